@@ -0,0 +1,145 @@
+//! Ban evasion detection through linked Minecraft accounts.
+//!
+//! Every time the bot learns the Minecraft account of a user, the link is stored. That happens when
+//! they are the subject of a screenshare and when their nickname is set to their IGN. Users who
+//! share a Minecraft account with a banned user are reported to staff in `support_bans`.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bridge_scrims::hypixel::{Player, UUID};
+use serenity::{
+    client::Context,
+    model::{guild::Member, id::UserId},
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use crate::consts::{CONFIG, DATABASE};
+
+/// How long the list of server bans is reused before it is fetched again
+const BANS_TTL: Duration = Duration::from_secs(10 * 60);
+
+lazy_static::lazy_static! {
+    /// The server bans as they were last fetched, most are permanent and not in the database
+    static ref BANS: Mutex<Option<(Instant, Vec<UserId>)>> = Mutex::new(None);
+}
+
+/// An account that shares a Minecraft account with the checked user
+struct Alt {
+    user: u64,
+    uuid: String,
+    ban: &'static str,
+}
+
+/// Stores that `user` plays on the Minecraft account `uuid` and checks them for ban evasion
+pub async fn link(ctx: &Context, user: UserId, uuid: &UUID) {
+    if let Err(e) = DATABASE.add_link(user.0, &uuid.to_string(), OffsetDateTime::now_utc()) {
+        tracing::error!("Could not link {} to {}: {}", user, uuid, e);
+    }
+    check(ctx, user).await;
+}
+
+/// Links the member to the Minecraft account named like their nickname, if it is a valid IGN
+pub async fn link_nickname(ctx: &Context, member: &Member) {
+    let nick = match &member.nick {
+        Some(nick) if is_ign(nick) => nick.clone(),
+        _ => return,
+    };
+    match Player::fetch_from_username(nick.clone()).await {
+        Ok(player) => link(ctx, member.user.id, &player.0).await,
+        Err(e) => tracing::debug!("Could not look up the nickname {} as an IGN: {}", nick, e),
+    }
+}
+
+/// Minecraft names are 3 to 16 letters, digits or underscores
+fn is_ign(name: &str) -> bool {
+    (3..=16).contains(&name.len()) && name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+/// The users that are banned from the server, fetched at most once every [`BANS_TTL`]
+async fn server_bans(ctx: &Context) -> Vec<UserId> {
+    if let Some((fetched, bans)) = &*BANS.lock().unwrap() {
+        if fetched.elapsed() < BANS_TTL {
+            return bans.clone();
+        }
+    }
+    let bans: Vec<UserId> = match CONFIG.guild.bans(&ctx.http).await {
+        Ok(bans) => bans.into_iter().map(|x| x.user.id).collect(),
+        Err(e) => {
+            tracing::error!("Could not fetch the server bans: {}", e);
+            return Vec::new();
+        }
+    };
+    *BANS.lock().unwrap() = Some((Instant::now(), bans.clone()));
+    bans
+}
+
+/// Warns staff if any of the Minecraft accounts of `user` belong to a banned user
+pub async fn check(ctx: &Context, user: UserId) {
+    let mut linked = Vec::new();
+    for uuid in DATABASE.fetch_links_for(user.0) {
+        for other in DATABASE.fetch_users_linked_to(&uuid) {
+            if other != user.0 {
+                linked.push((other, uuid.clone()));
+            }
+        }
+    }
+    // Only users who share an account with someone else need the ban lists
+    if linked.is_empty() {
+        return;
+    }
+    let bans = server_bans(ctx).await;
+    let server_bans = DATABASE.fetch_unbans();
+    let scrim_bans = DATABASE.fetch_scrim_unbans();
+    let ban_of = |id: u64| {
+        if server_bans.iter().any(|x| x.id == id) || bans.contains(&UserId(id)) {
+            Some("server banned")
+        } else if scrim_bans.iter().any(|x| x.id == id) {
+            Some("scrim banned")
+        } else {
+            None
+        }
+    };
+
+    let alts: Vec<Alt> = linked
+        .into_iter()
+        .filter_map(|(other, uuid)| {
+            ban_of(other).map(|ban| Alt {
+                user: other,
+                uuid,
+                ban,
+            })
+        })
+        .collect();
+    if alts.is_empty() {
+        return;
+    }
+
+    tracing::info!("{} shares a Minecraft account with a banned user", user);
+    let result = CONFIG
+        .support_bans
+        .send_message(&ctx.http, |msg| {
+            msg.embed(|embed| {
+                embed
+                    .title("Possible ban evasion")
+                    .description(format!(
+                        "<@{}> shares a Minecraft account with banned users:",
+                        user
+                    ))
+                    .color(Color::ORANGE);
+                for alt in &alts {
+                    embed.field(
+                        format!("Minecraft account {}", alt.uuid),
+                        format!("<@{}> is {}", alt.user, alt.ban),
+                        false,
+                    );
+                }
+                embed
+            })
+        })
+        .await;
+    if let Err(e) = result {
+        tracing::error!("Could not report possible ban evasion of {}: {}", user, e);
+    }
+}
@@ -0,0 +1,325 @@
+//! Ban appeals.
+//!
+//! Ban DMs carry an "Appeal" button. Pressing it asks the user to send their appeal as a DM, which
+//! is then posted to the `appeals` channel where staff can accept or deny it. Users can only have
+//! one open appeal per ban. Server banned users first have to join `appeal_guild`, as the bot can
+//! not read their DMs otherwise.
+
+use std::time::Duration;
+
+use serenity::{
+    async_trait,
+    client::Context,
+    model::{
+        id::UserId,
+        interactions::{
+            message_component::{ButtonStyle, MessageComponentInteraction},
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+        },
+    },
+    utils::Color,
+};
+
+use crate::commands::unban::{UnbanEntry, UnbanType};
+use crate::commands::Button;
+use crate::consts::{CONFIG, DATABASE};
+use crate::db::{AppealStatus, PunishmentKind};
+
+/// How long users have to send their appeal after pressing the button
+const APPEAL_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Handles the buttons of the appeal flow, which all start with `appeal:`
+pub struct Appeal;
+
+#[async_trait]
+impl Button for Appeal {
+    fn prefix(&self) -> String {
+        String::from("appeal")
+    }
+    async fn click(
+        &self,
+        ctx: &Context,
+        interaction: &MessageComponentInteraction,
+    ) -> crate::Result<()> {
+        let parts: Vec<&str> = interaction.data.custom_id.split(':').collect();
+        match parts.as_slice() {
+            [_, "accept", id] => review(ctx, interaction, id.parse()?, true).await,
+            [_, "deny", id] => review(ctx, interaction, id.parse()?, false).await,
+            [_, kind] => submit(ctx, interaction, kind.parse()?).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+async fn respond(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    content: &str,
+) -> serenity::Result<()> {
+    interaction
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.interaction_response_data(|data| {
+                data.content(content)
+                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+            })
+        })
+        .await
+}
+
+async fn is_banned(ctx: &Context, user: UserId, kind: PunishmentKind) -> serenity::Result<bool> {
+    Ok(match kind {
+        PunishmentKind::Ban => CONFIG
+            .guild
+            .bans(&ctx.http)
+            .await?
+            .iter()
+            .any(|x| x.user.id == user),
+        _ => DATABASE.fetch_scrim_unbans().iter().any(|x| x.id == user.0),
+    })
+}
+
+/// Whether the user shares a server with the bot, which is needed to read their DMs. Server banned
+/// users are only reachable through `appeal_guild`.
+async fn can_receive_dms(ctx: &Context, user: UserId) -> bool {
+    match CONFIG.appeal_guild {
+        Some(guild) => guild.member(&ctx.http, user).await.is_ok(),
+        None => false,
+    }
+}
+
+async fn submit(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    kind: PunishmentKind,
+) -> crate::Result<()> {
+    let user = &interaction.user;
+    if !is_banned(ctx, user.id, kind).await? {
+        respond(ctx, interaction, "You are no longer banned.").await?;
+        return Ok(());
+    }
+    if DATABASE.fetch_open_appeal(user.id.0, kind).is_some() {
+        respond(
+            ctx,
+            interaction,
+            "You already have an open appeal for this ban. Please wait for staff to review it.",
+        )
+        .await?;
+        return Ok(());
+    }
+    if kind == PunishmentKind::Ban && !can_receive_dms(ctx, user.id).await {
+        let content = match &CONFIG.appeal_invite {
+            Some(invite) => format!(
+                "Please join {} first, the bot can not read your messages otherwise.",
+                invite
+            ),
+            None => String::from("Server bans can not be appealed here."),
+        };
+        respond(ctx, interaction, &content).await?;
+        return Ok(());
+    }
+    respond(
+        ctx,
+        interaction,
+        "Please send your appeal as a single message in this channel within 15 minutes.",
+    )
+    .await?;
+
+    let reply = interaction
+        .channel_id
+        .await_reply(&ctx)
+        .author_id(user.id)
+        .timeout(APPEAL_TIMEOUT)
+        .await;
+    let reply = match reply {
+        Some(reply) => reply,
+        None => {
+            interaction
+                .channel_id
+                .say(
+                    &ctx.http,
+                    "Your appeal timed out. Press the button again to start over.",
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+    // The button may have been pressed twice while waiting for the message
+    if DATABASE.fetch_open_appeal(user.id.0, kind).is_some() {
+        return Ok(());
+    }
+
+    let case = DATABASE
+        .fetch_punishments_for(user.id.0)
+        .iter()
+        .filter(|x| x.kind == kind)
+        .map(|x| x.id)
+        .max();
+    let id = DATABASE.add_appeal(user.id.0, kind, case, &reply.content)?;
+    let mut text = reply.content.clone();
+    if text.len() > 1000 {
+        let mut end = 1000;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("...");
+    }
+
+    CONFIG
+        .appeals
+        .unwrap_or(CONFIG.support_bans)
+        .send_message(&ctx.http, |msg| {
+            msg.embed(|embed| {
+                embed
+                    .title(format!("Appeal #{}: {}", id, kind))
+                    .field("User", format!("<@{}>", user.id), false)
+                    .field(
+                        "Case",
+                        case.map_or_else(|| "unknown".to_string(), |x| format!("#{}", x)),
+                        false,
+                    )
+                    .field("Appeal", text, false)
+                    .color(Color::BLURPLE)
+            })
+            .components(|c| {
+                c.create_action_row(|row| {
+                    row.create_button(|b| {
+                        b.label("Accept")
+                            .style(ButtonStyle::Success)
+                            .custom_id(format!("appeal:accept:{}", id))
+                    })
+                    .create_button(|b| {
+                        b.label("Deny")
+                            .style(ButtonStyle::Danger)
+                            .custom_id(format!("appeal:deny:{}", id))
+                    })
+                })
+            })
+        })
+        .await?;
+    interaction
+        .channel_id
+        .say(
+            &ctx.http,
+            "Your appeal has been submitted. You will be notified once staff have reviewed it.",
+        )
+        .await?;
+    Ok(())
+}
+
+async fn review(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    id: u64,
+    accepted: bool,
+) -> crate::Result<()> {
+    let allowed = interaction.member.as_ref().is_some_and(|member| {
+        member.roles.contains(&CONFIG.support) || member.roles.contains(&CONFIG.staff)
+    });
+    if !allowed {
+        respond(ctx, interaction, "You are not allowed to review appeals.").await?;
+        return Ok(());
+    }
+    let appeal = match DATABASE.fetch_appeal(id) {
+        Some(appeal) if appeal.status == AppealStatus::Open => appeal,
+        _ => {
+            respond(ctx, interaction, "This appeal has already been reviewed.").await?;
+            return Ok(());
+        }
+    };
+    let reviewer = interaction.user.id;
+
+    if accepted {
+        let user = UserId(appeal.user);
+        let (unban_type, entry) = match appeal.kind {
+            PunishmentKind::Ban => (
+                UnbanType::Server,
+                CONFIG
+                    .guild
+                    .bans(&ctx.http)
+                    .await?
+                    .into_iter()
+                    .find(|x| x.user.id == user)
+                    .map(UnbanEntry::Server),
+            ),
+            _ => (
+                UnbanType::Scrim,
+                DATABASE
+                    .fetch_scrim_unbans()
+                    .into_iter()
+                    .find(|x| x.id == user.0)
+                    .map(UnbanEntry::Scrim),
+            ),
+        };
+        // The ban may have expired or been lifted while the appeal was open
+        if let Some(entry) = entry {
+            let result = unban_type
+                .unban(
+                    &ctx.http,
+                    Some(reviewer),
+                    entry,
+                    format!("Appeal #{} accepted", id),
+                )
+                .await;
+            if let Err(e) = result {
+                respond(
+                    ctx,
+                    interaction,
+                    &format!("Could not unban the user: {}", e),
+                )
+                .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let status = if accepted {
+        AppealStatus::Accepted
+    } else {
+        AppealStatus::Denied
+    };
+    DATABASE.set_appeal_status(id, status, reviewer.0)?;
+    interaction
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|data| {
+                    data.content(format!(
+                        "{} by <@{}>",
+                        if accepted { "Accepted" } else { "Denied" },
+                        reviewer
+                    ))
+                    .components(|c| c.set_action_rows(Default::default()))
+                })
+        })
+        .await?;
+
+    let dm_result = UserId(appeal.user)
+        .create_dm_channel(&ctx.http)
+        .await?
+        .send_message(&ctx.http, |msg| {
+            msg.embed(|embed| {
+                embed
+                    .title(format!(
+                        "Your appeal has been {}",
+                        if accepted { "accepted" } else { "denied" }
+                    ))
+                    .description(match appeal.case {
+                        Some(case) => format!(
+                            "Your appeal against case #{} ({}) has been reviewed by staff.",
+                            case, appeal.kind
+                        ),
+                        None => format!("Your {} appeal has been reviewed by staff.", appeal.kind),
+                    })
+                    .color(if accepted {
+                        Color::DARK_GREEN
+                    } else {
+                        Color::RED
+                    })
+            })
+        })
+        .await;
+    if let Err(e) = dm_result {
+        tracing::error!("Could not DM {} about their appeal: {}", appeal.user, e);
+    }
+    Ok(())
+}
@@ -0,0 +1,337 @@
+//! Automatic moderation, configured in the `[automod]` section of the config.
+//!
+//! Every guild message is checked against the configured rules. A message that breaks a rule is
+//! deleted, the rule's action is applied to its author and the hit is logged to `automod.log`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serenity::{
+    client::Context,
+    model::{channel::Message, id::UserId},
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use crate::commands::ban::BanType;
+use crate::consts::{AutomodAction, CONFIG, DATABASE};
+use crate::db::{JobKind, PunishmentKind};
+
+/// How long users are timed out for when the rule has no duration
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+lazy_static::lazy_static! {
+    /// The recent messages of every user, used by the flood and duplicate rules
+    static ref HISTORY: Mutex<HashMap<UserId, VecDeque<(Instant, String)>>> =
+        Mutex::new(HashMap::new());
+    static ref INVITE: Regex =
+        Regex::new(r"(?i)(discord\.gg|discord(app)?\.com/invite)/[\w-]+").unwrap();
+    static ref BLOCKED: Vec<Regex> = CONFIG
+        .automod
+        .blocked
+        .iter()
+        .flat_map(|rule| {
+            rule.words
+                .iter()
+                .map(|word| format!(r"(?i)\b{}\b", regex::escape(word)))
+                .chain(rule.regexes.iter().cloned())
+        })
+        .filter_map(|pattern| match Regex::new(&pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                tracing::error!("Invalid automod pattern {}: {}", pattern, e);
+                None
+            }
+        })
+        .collect();
+}
+
+/// A broken rule
+struct Hit {
+    rule: &'static str,
+    action: AutomodAction,
+    duration: Option<u64>,
+}
+
+/// Checks a message against the automod rules, returning whether it was removed
+pub async fn check(ctx: &Context, msg: &Message) -> bool {
+    if msg.guild_id != Some(CONFIG.guild) || is_exempt(msg) {
+        return false;
+    }
+    let hit = match find_hit(ctx, msg).await {
+        Some(hit) => hit,
+        None => return false,
+    };
+    tracing::info!("Automod rule {} hit by {}", hit.rule, msg.author.tag());
+    if let Err(e) = msg.delete(&ctx.http).await {
+        tracing::error!("Could not delete message of {}: {}", msg.author.tag(), e);
+    }
+    let case = match punish(ctx, msg, &hit).await {
+        Ok(case) => case,
+        Err(e) => {
+            tracing::error!(
+                "Could not apply automod action to {}: {}",
+                msg.author.tag(),
+                e
+            );
+            None
+        }
+    };
+    log(ctx, msg, &hit, case).await;
+    true
+}
+
+fn is_exempt(msg: &Message) -> bool {
+    let roles = match &msg.member {
+        Some(member) => &member.roles,
+        None => return false,
+    };
+    roles.iter().any(|role| {
+        [CONFIG.support, CONFIG.trial_support, CONFIG.staff].contains(role)
+            || CONFIG.automod.exempt_roles.contains(role)
+    })
+}
+
+async fn find_hit(ctx: &Context, msg: &Message) -> Option<Hit> {
+    let automod = &CONFIG.automod;
+    if let Some(rule) = &automod.blocked {
+        if BLOCKED.iter().any(|x| x.is_match(&msg.content)) {
+            return Some(Hit {
+                rule: "blocked content",
+                action: rule.action,
+                duration: rule.duration,
+            });
+        }
+    }
+    if let Some(rule) = &automod.invites {
+        if INVITE.is_match(&msg.content) && !invites_allowed(ctx, msg, &rule.allowed_channels).await
+        {
+            return Some(Hit {
+                rule: "invite link",
+                action: rule.action,
+                duration: rule.duration,
+            });
+        }
+    }
+    if let Some(rule) = &automod.mentions {
+        if msg.mentions.len() + msg.mention_roles.len() > rule.max {
+            return Some(Hit {
+                rule: "mass mention",
+                action: rule.action,
+                duration: rule.duration,
+            });
+        }
+    }
+    track(msg)
+}
+
+async fn invites_allowed(
+    ctx: &Context,
+    msg: &Message,
+    allowed: &[serenity::model::id::ChannelId],
+) -> bool {
+    if allowed.contains(&msg.channel_id) {
+        return true;
+    }
+    let category = msg
+        .channel_id
+        .to_channel_cached(&ctx.cache)
+        .await
+        .and_then(|x| x.guild())
+        .and_then(|x| x.category_id);
+    category.is_some_and(|x| allowed.contains(&x))
+}
+
+/// Remembers the message and checks the flood and duplicate rules
+fn track(msg: &Message) -> Option<Hit> {
+    let automod = &CONFIG.automod;
+    let window = automod
+        .flood
+        .iter()
+        .map(|x| x.seconds)
+        .chain(automod.duplicate.iter().map(|x| x.seconds))
+        .max()?;
+    let now = Instant::now();
+    let content = msg.content.trim().to_lowercase();
+
+    let mut history = HISTORY.lock().unwrap();
+    // Users who have not written within the window are forgotten, so only recent writers are kept
+    history.retain(|_, recent| {
+        recent
+            .back()
+            .is_some_and(|(sent, _)| now - *sent <= Duration::from_secs(window))
+    });
+    let recent = history.entry(msg.author.id).or_default();
+    while recent
+        .front()
+        .is_some_and(|(sent, _)| now - *sent > Duration::from_secs(window))
+    {
+        recent.pop_front();
+    }
+    recent.push_back((now, content.clone()));
+
+    let within = |seconds: u64| {
+        recent
+            .iter()
+            .filter(move |(sent, _)| now - *sent <= Duration::from_secs(seconds))
+    };
+    let mut hit = None;
+    if let Some(rule) = &automod.flood {
+        if within(rule.seconds).count() >= rule.messages {
+            hit = Some(Hit {
+                rule: "message flood",
+                action: rule.action,
+                duration: rule.duration,
+            });
+        }
+    }
+    if let Some(rule) = &automod.duplicate {
+        let duplicates = within(rule.seconds)
+            .filter(|(_, text)| !text.is_empty() && *text == content)
+            .count();
+        if hit.is_none() && duplicates >= rule.count {
+            hit = Some(Hit {
+                rule: "duplicate messages",
+                action: rule.action,
+                duration: rule.duration,
+            });
+        }
+    }
+    if hit.is_some() {
+        // Start counting again, so that the user is not punished for every following message
+        recent.clear();
+    }
+    hit
+}
+
+/// Applies the action of the hit, returning the case number
+async fn punish(ctx: &Context, msg: &Message, hit: &Hit) -> serenity::Result<Option<i64>> {
+    let reason = format!("Automod: {}", hit.rule);
+    let duration = hit.duration.map(Duration::from_secs);
+    let user = &msg.author;
+    match hit.action {
+        AutomodAction::Delete => Ok(None),
+        AutomodAction::Warn => {
+            let now = OffsetDateTime::now_utc();
+            let expires_at = duration.map(|x| now + x);
+            let bot = ctx.cache.current_user_id().await;
+            if let Err(e) = DATABASE.add_warning(user.id.0, now, &reason, bot.0, expires_at) {
+                tracing::error!("Could not add warning for {}: {}", user.tag(), e);
+                return Ok(None);
+            }
+            let case = crate::commands::case::record(
+                PunishmentKind::Warning,
+                user.id.0,
+                None,
+                &reason,
+                expires_at,
+            );
+            let dm_result = user
+                .dm(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.title("You received a warning")
+                            .field("Reason", format!("`{}`", reason), false)
+                            .color(Color::ORANGE)
+                    })
+                })
+                .await;
+            if let Err(e) = dm_result {
+                tracing::error!("Could not DM {} about their warning: {}", user.tag(), e);
+            }
+            tokio::spawn(crate::escalation::check(
+                ctx.clone(),
+                user.id,
+                PunishmentKind::Warning,
+            ));
+            Ok(case)
+        }
+        AutomodAction::Timeout => {
+            let duration = duration.unwrap_or(DEFAULT_TIMEOUT);
+            let end = chrono::Utc::now() + chrono::Duration::seconds(duration.as_secs() as i64);
+            let mut member = CONFIG.guild.member(&ctx.http, user.id).await?;
+            member
+                .disable_communication_until_datetime(&ctx.http, end)
+                .await?;
+            let until = OffsetDateTime::from_unix_timestamp(end.timestamp()).ok();
+            if let Some(until) = until {
+                if let Err(e) = DATABASE.add_timeout(user.id.0, until) {
+                    tracing::error!("Could not store the timeout of {}: {}", user.tag(), e);
+                }
+                crate::scheduler::schedule(JobKind::Untimeout, user.id.0, "", until);
+            }
+            let case = crate::commands::case::record(
+                PunishmentKind::Timeout,
+                user.id.0,
+                None,
+                &reason,
+                until,
+            );
+            tokio::spawn(crate::escalation::check(
+                ctx.clone(),
+                user.id,
+                PunishmentKind::Timeout,
+            ));
+            Ok(case)
+        }
+        AutomodAction::ScrimBan => {
+            let unban_date = duration
+                .or_else(|| BanType::Scrim.default_duration())
+                .map(|x| OffsetDateTime::now_utc() + x);
+            let outcome = BanType::Scrim
+                .apply(&ctx.http, &ctx.cache, user, None, reason, unban_date, 0)
+                .await;
+            outcome.result?;
+            tokio::spawn(crate::escalation::check(
+                ctx.clone(),
+                user.id,
+                PunishmentKind::ScrimBan,
+            ));
+            // The case is already shown in the ban log
+            Ok(None)
+        }
+    }
+}
+
+async fn log(ctx: &Context, msg: &Message, hit: &Hit, case: Option<i64>) {
+    let channel = match CONFIG.automod.log {
+        Some(channel) => channel,
+        None => return,
+    };
+    let action = match hit.action {
+        AutomodAction::Delete => "Delete",
+        AutomodAction::Warn => "Warn",
+        AutomodAction::Timeout => "Timeout",
+        AutomodAction::ScrimBan => "Scrim ban",
+    };
+    let mut content = msg.content.clone();
+    if content.len() > 1000 {
+        let mut end = 1000;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        content.truncate(end);
+        content.push_str("...");
+    }
+    let result = channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title(format!("Automod: {}", hit.rule))
+                    .field("User", format!("<@{}>", msg.author.id), false)
+                    .field("Channel", format!("<#{}>", msg.channel_id), false)
+                    .field("Action", action, false);
+                if let Some(case) = case {
+                    e.field("Case", format!("#{}", case), false);
+                }
+                if !content.is_empty() {
+                    e.field("Message", content, false);
+                }
+                e.color(Color::ORANGE)
+            })
+        })
+        .await;
+    if let Err(e) = result {
+        tracing::error!("Could not log automod hit: {}", e);
+    }
+}
@@ -4,25 +4,31 @@ use serenity::{
     client::{Cache, Context},
     http::Http,
     model::{
+        guild::Member,
         id::UserId,
         interactions::{
             application_command::{
                 ApplicationCommandInteraction, ApplicationCommandOptionType,
                 ApplicationCommandPermissionType,
             },
+            message_component::ButtonStyle,
             InteractionApplicationCommandCallbackDataFlags,
         },
+        user::User,
     },
+    utils::Color,
 };
 
-use std::{sync::Arc, time::Duration};
+use std::time::Duration;
 use time::OffsetDateTime;
 
-use crate::{commands::Command, consts::CONFIG};
+use crate::{
+    commands::Command,
+    consts::CONFIG,
+    db::{JobKind, PunishmentKind},
+};
 use bridge_scrims::interact_opts::InteractOpts;
 
-use super::unban::{UnbanEntry, UnbanType};
-
 fn format_db_error(e: &sqlite::Error) -> String {
     if let Some(19) = e.code {
         "WARNING: this ban already exists.".to_string()
@@ -31,102 +37,304 @@ fn format_db_error(e: &sqlite::Error) -> String {
     }
 }
 
+/// Whether `staff` is allowed to punish `member`, which requires a higher top role
+pub async fn outranks(cache: &Cache, staff: &Member, member: &Member) -> bool {
+    let roles = member.roles(&cache).await.unwrap_or_default();
+    let cmd_roles = staff.roles(&cache).await.unwrap_or_default();
+
+    let top_role = roles.iter().max();
+    let cmd_top_role = cmd_roles.iter().max();
+
+    top_role < cmd_top_role && !member.user.bot
+}
+
 pub enum BanType {
     Server,
     Scrim,
+    /// Removes the user from the server without banning them
+    Kick,
+    /// Bans and immediately unbans the user to delete their recent messages
+    SoftBan,
+}
+
+/// The outcome of [`BanType::apply`]
+pub struct BanOutcome {
+    /// The embed describing the ban, as it was sent to `support_bans`
+    pub embed: CreateEmbed,
+    /// Whether discord accepted the ban
+    pub result: serenity::Result<()>,
+    /// Whether the ban could be stored in the database
+    pub db_result: Result<(), sqlite::Error>,
 }
 
 impl BanType {
+    /// The duration of a ban when none is given, `None` meaning forever
+    pub fn default_duration(&self) -> Option<Duration> {
+        match self {
+            BanType::Server | BanType::Kick | BanType::SoftBan => None,
+            BanType::Scrim => Some(Duration::from_secs(30 * 86400)),
+        }
+    }
+
+    pub fn punishment_kind(&self) -> PunishmentKind {
+        match self {
+            BanType::Server => PunishmentKind::Ban,
+            BanType::Scrim => PunishmentKind::ScrimBan,
+            BanType::Kick => PunishmentKind::Kick,
+            BanType::SoftBan => PunishmentKind::SoftBan,
+        }
+    }
+
+    /// How the action is called in messages, e.g. "Could not kick ..."
+    fn verb(&self) -> &'static str {
+        match self {
+            BanType::Server | BanType::Scrim => "ban",
+            BanType::Kick => "kick",
+            BanType::SoftBan => "softban",
+        }
+    }
+
+    /// Whether the user stays banned after the action
+    fn is_ban(&self) -> bool {
+        matches!(self, BanType::Server | BanType::Scrim)
+    }
+
+    /// Whether the user can appeal the ban with the button of the ban DM. Server banned users
+    /// need a server shared with the bot to send their appeal.
+    fn is_appealable(&self) -> bool {
+        match self {
+            BanType::Server => CONFIG.appeal_guild.is_some(),
+            BanType::Scrim => true,
+            BanType::Kick | BanType::SoftBan => false,
+        }
+    }
+
+    /// Bans the user given in `opts`, which are either the options of the command itself or
+    /// those of one of its subcommands
     pub async fn exec(
         &self,
-        http: &Http,
-        cache: &Cache,
+        ctx: &Context,
         command: &ApplicationCommandInteraction,
+        opts: &(impl InteractOpts + Sync),
     ) -> crate::Result<()> {
+        let http = &ctx.http;
+        let cache = &ctx.cache;
         let cmd_member = command.clone().member.unwrap();
 
-        let user = UserId(command.get_str("user").unwrap().parse()?)
+        let user = UserId(opts.get_str("user").unwrap().parse()?)
             .to_user(&http)
             .await?;
         let id = user.id;
 
-        let reason = command
+        let reason = opts
             .get_str("reason")
             .unwrap_or_else(|| String::from("No reason given."));
 
-        let duration = command.get_u64("duration");
-        let factor = command.get_u64("type");
+        let duration = opts.get_u64("duration");
+        let factor = opts.get_u64("type");
 
-        let seconds = match (self, duration, factor) {
-            (_, Some(d), None) => Some(d * 86400),
-            (_, Some(d), Some(f)) => Some(d * f),
-            (BanType::Server, None, _) => None,
-            (BanType::Scrim, None, _) => Some(30 * 86400),
+        let duration = match (duration, factor) {
+            (Some(d), None) => Some(Duration::from_secs(d * 86400)),
+            (Some(d), Some(f)) => Some(Duration::from_secs(d * f)),
+            (None, _) => self.default_duration(),
         };
-        let unban_date = seconds.map(|seconds| {
-            let now = OffsetDateTime::now_utc();
-            let duration = Duration::from_secs(seconds);
-            now + duration
-        });
+        let unban_date = duration.map(|duration| OffsetDateTime::now_utc() + duration);
 
-        let mut member = CONFIG.guild.member(&http, id).await?;
-        let roles = member.roles(&cache).await.unwrap_or_default();
-        let cmd_roles = cmd_member.roles(&cache).await.unwrap_or_default();
+        let member = CONFIG.guild.member(&http, id).await?;
+        if !outranks(cache, &cmd_member, &member).await {
+            command
+                .create_interaction_response(&http, |resp| {
+                    resp.interaction_response_data(|data| {
+                        data.content(format!(
+                            "You do not have permission to {} {}",
+                            self.verb(),
+                            user.tag()
+                        ))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
 
-        let top_role = roles.iter().max();
-        let cmd_top_role = cmd_roles.iter().max();
+        if crate::consts::DATABASE.fetch_freezes_for(id.0).is_some() {
+            command
+                .create_followup_message(http, |msg| {
+                    msg.content(format!(
+                        "Unfreezing {} before {}ing them.",
+                        user.tag(),
+                        self.verb()
+                    ))
+                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+                .await?;
+        }
+
+        let do_dmd = opts.get_bool("dmd").unwrap_or(false);
+        let dmd = if do_dmd { 7 } else { 0 };
+        let BanOutcome {
+            mut embed,
+            result,
+            db_result,
+        } = self
+            .apply(
+                http,
+                cache,
+                &user,
+                Some(command.user.id),
+                reason,
+                unban_date,
+                dmd,
+            )
+            .await;
 
-        if top_role >= cmd_top_role || user.bot {
+        if let Err(ref e) = result {
             command
                 .create_interaction_response(&http, |resp| {
                     resp.interaction_response_data(|data| {
-                        data.content(format!("You do not have permission to ban {}", user.tag()))
+                        data.content(format!("Could not {} {}: {}", self.verb(), user.tag(), e))
                             .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
                     })
                 })
                 .await?;
-            return Ok(());
+        } else if command.channel_id != CONFIG.support_bans {
+            command
+                .create_interaction_response(&http, |resp| {
+                    resp.interaction_response_data(|data| {
+                        match (result.as_ref(), db_result.as_ref()) {
+                            (Ok(_), Err(e)) => {
+                                embed.description(format_db_error(e));
+                                data.add_embed(embed.clone())
+                            }
+                            _ => data.add_embed(embed.clone()),
+                        }
+                    })
+                })
+                .await?;
         }
 
+        result?;
+
+        tokio::spawn(crate::escalation::check(
+            ctx.clone(),
+            id,
+            self.punishment_kind(),
+        ));
+
+        Ok(())
+    }
+
+    /// Bans the user without any permission checks. This DMs them, unfreezes them if needed,
+    /// records the case and logs the ban to `support_bans`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn apply(
+        &self,
+        http: &Http,
+        cache: &Cache,
+        user: &User,
+        staff: Option<UserId>,
+        reason: String,
+        unban_date: Option<OffsetDateTime>,
+        dmd: u8,
+    ) -> BanOutcome {
+        let id = user.id;
+
         let mut embed = CreateEmbed::default();
-        embed.title(format!("{} recieved a ban", user.tag()));
+        embed.title(match self {
+            Self::Server | Self::Scrim => format!("{} recieved a ban", user.tag()),
+            Self::Kick => format!("{} was kicked", user.tag()),
+            Self::SoftBan => format!("{} was softbanned", user.tag()),
+        });
         embed.field("User", format!("<@{}>", id), false);
-        if let Some(unban_date) = unban_date {
+        if self.is_ban() {
             embed.field(
                 "Duration",
-                format!("<t:{}:R>", unban_date.unix_timestamp()),
+                unban_date.map_or_else(
+                    || "forever".to_string(),
+                    |x| format!("<t:{}:R>", x.unix_timestamp()),
+                ),
                 false,
             );
-        } else {
-            embed.field("Duration", "forever", false);
         }
         embed.field("Reason", format!("`{}`", reason), false);
-        embed.field("Staff", format!("<@{}>", command.user.id), false);
-        if matches!(self, BanType::Server) {
-            embed.description("Appeal at http://appeal.bridgescrims.com/");
+        embed.field(
+            "Staff",
+            staff.map_or_else(|| "Automatic".to_string(), |x| format!("<@{}>", x)),
+            false,
+        );
+        if self.is_appealable() {
+            embed.description(match (self, &CONFIG.appeal_invite) {
+                (BanType::Server, Some(invite)) => format!(
+                    "You can appeal this ban using the button below after joining {}",
+                    invite
+                ),
+                _ => String::from("You can appeal this ban using the button below."),
+            });
         }
-        let dm_result = user.dm(&http, |msg| msg.set_embed(embed.clone())).await;
+        let dm_result = user
+            .dm(&http, |msg| {
+                msg.set_embed(embed.clone());
+                if !self.is_appealable() {
+                    return msg;
+                }
+                msg.components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_button(|b| {
+                            b.label("Appeal")
+                                .style(ButtonStyle::Primary)
+                                .custom_id(format!("appeal:{}", self.punishment_kind().as_str()))
+                        })
+                    })
+                })
+            })
+            .await;
         if let Err(e) = dm_result {
-            tracing::error!("Could not DM user {} about their ban: {}", user.tag(), e);
+            tracing::error!(
+                "Could not DM user {} about their {}: {}",
+                user.tag(),
+                self.verb(),
+                e
+            );
         }
         embed.description("");
         let mut result = Ok(());
         let mut db_result = Ok(());
+        // Scrim bans are stored even if some roles could not be removed
+        let mut scrim_banned = false;
 
         if crate::consts::DATABASE.fetch_freezes_for(id.0).is_some() {
-            super::unfreeze::unfreeze_user(http, id).await?;
-            command
-                .create_followup_message(http, |msg| {
-                    msg.content(format!("Unfreezing {} before banning them.", user.tag()))
-                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
-                })
-                .await?;
+            let unfreeze = super::unfreeze::unfreeze_user(
+                http,
+                id,
+                staff,
+                &format!("Unfrozen before a {}", self.verb()),
+            )
+            .await;
+            if let Err(e) = unfreeze {
+                tracing::error!(
+                    "Could not unfreeze {} before {}ing them: {}",
+                    id,
+                    self.verb(),
+                    e
+                );
+            }
         }
 
         match self {
+            Self::Kick => {
+                result = CONFIG.guild.kick_with_reason(&http, id, &reason).await;
+            }
+            Self::SoftBan => {
+                // Always remove the last 7 days of messages, that is the point of a softban
+                result = CONFIG
+                    .guild
+                    .ban_with_reason(&http, id, 7, reason.clone())
+                    .await;
+                if result.is_ok() {
+                    result = CONFIG.guild.unban(&http, id).await;
+                }
+            }
             Self::Server => {
-                let do_dmd = command.get_bool("dmd").unwrap_or(false);
-                let dmd = if do_dmd { 7 } else { 0 };
                 if let Some(unban_date) = unban_date {
                     if crate::consts::DATABASE
                         .fetch_unbans()
@@ -147,13 +355,6 @@ impl BanType {
                     .guild
                     .ban_with_reason(&http, id, dmd, reason.clone())
                     .await;
-
-                if result.is_ok() {
-                    CONFIG
-                        .support_bans
-                        .send_message(&http, |msg| msg.set_embed(embed.clone()))
-                        .await?;
-                }
             }
             Self::Scrim => {
                 if crate::consts::DATABASE
@@ -165,61 +366,251 @@ impl BanType {
                     db_result = crate::consts::DATABASE.modify_unban_date(
                         "ScheduledScrimUnbans",
                         *id.as_u64(),
+                        // NOTE: In the case of a `ScrimBan`, this is always `Some`
                         unban_date.unwrap(),
                     );
+                    scrim_banned = db_result.is_ok();
                 } else {
-                    let mut removed_roles = Vec::new();
-                    for role in roles.iter().filter(|x| !x.managed) {
-                        if let Err(e) = member.remove_role(&http, role).await {
-                            result = result.and(Err(e));
-                        } else {
-                            removed_roles.push(role.id);
+                    match CONFIG.guild.member(&http, id).await {
+                        Ok(mut member) => {
+                            let roles = member.roles(&cache).await.unwrap_or_default();
+                            let mut removed_roles = Vec::new();
+                            for role in roles.iter().filter(|x| !x.managed) {
+                                if let Err(e) = member.remove_role(&http, role).await {
+                                    result = result.and(Err(e));
+                                } else {
+                                    removed_roles.push(role.id);
+                                }
+                            }
+                            result = result.and(member.add_role(&http, CONFIG.banned.0).await);
+
+                            db_result = crate::consts::DATABASE.add_scrim_unban(
+                                *id.as_u64(),
+                                // NOTE: In the case of a `ScrimBan`, this is always `Some`
+                                unban_date.unwrap(),
+                                &removed_roles.into(),
+                            );
+                            scrim_banned = db_result.is_ok();
                         }
+                        Err(e) => result = Err(e),
                     }
-                    result = result.and(member.add_role(&http, CONFIG.banned.0).await);
+                }
+            }
+        }
 
-                    db_result = crate::consts::DATABASE.add_scrim_unban(
-                        *id.as_u64(),
-                        // NOTE: In the case of a `ScrimBan`, this is always `Some`
-                        unban_date.unwrap(),
-                        &removed_roles.into(),
-                    );
+        let applied = match self {
+            Self::Scrim => scrim_banned,
+            _ => result.is_ok(),
+        };
+        if applied {
+            if let Some(case) = super::case::record(
+                self.punishment_kind(),
+                id.0,
+                staff.map(|x| x.0),
+                &reason,
+                unban_date,
+            ) {
+                embed.field("Case", format!("#{}", case), false);
+            }
+        }
+        if let (Some(unban_date), Ok(()), true) = (unban_date, &db_result, applied) {
+            match self {
+                Self::Server => crate::scheduler::schedule(JobKind::Unban, id.0, "", unban_date),
+                Self::Scrim => {
+                    crate::scheduler::schedule(JobKind::ScrimUnban, id.0, "", unban_date)
                 }
+                _ => {}
+            }
+        }
+        // Failed bans are not logged, as nothing happened
+        if applied {
+            let log = CONFIG
+                .support_bans
+                .send_message(&http, |msg| msg.set_embed(embed.clone()))
+                .await;
+            if let Err(e) = log {
+                tracing::error!("Could not log the ban of {}: {}", user.tag(), e);
+            }
+        }
+
+        BanOutcome {
+            embed,
+            result,
+            db_result,
+        }
+    }
+}
 
-                CONFIG
-                    .support_bans
-                    .send_message(&http, |msg| msg.set_embed(embed.clone()))
+impl BanType {
+    /// Changes the reason or expiry of an existing ban and logs the difference to `support_bans`
+    pub async fn edit(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+        opts: &(impl InteractOpts + Sync),
+    ) -> crate::Result<()> {
+        let http = &ctx.http;
+        let user = UserId(opts.get_str("user").unwrap().parse()?)
+            .to_user(&http)
+            .await?;
+        let id = user.id;
+
+        let expiry = match self {
+            Self::Server => {
+                if !CONFIG
+                    .guild
+                    .bans(&http)
+                    .await?
+                    .iter()
+                    .any(|x| x.user.id == id)
+                {
+                    None
+                } else {
+                    Some(
+                        crate::consts::DATABASE
+                            .fetch_unbans()
+                            .into_iter()
+                            .find(|x| x.id == id.0)
+                            .map(|x| x.date),
+                    )
+                }
+            }
+            Self::Scrim => crate::consts::DATABASE
+                .fetch_scrim_unbans()
+                .into_iter()
+                .find(|x| x.id == id.0)
+                .map(|x| Some(x.date)),
+            // Nothing remains of these to be edited
+            Self::Kick | Self::SoftBan => None,
+        };
+        let old_expiry = match expiry {
+            Some(expiry) => expiry,
+            None => {
+                command
+                    .create_interaction_response(&http, |resp| {
+                        resp.interaction_response_data(|data| {
+                            data.content(format!("{} is not banned", user.tag()))
+                                .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                        })
+                    })
                     .await?;
+                return Ok(());
             }
-        }
+        };
 
-        if let Err(ref e) = result {
+        let permanent = opts.get_bool("permanent").unwrap_or(false);
+        let new_expiry = match (opts.get_u64("duration"), opts.get_u64("type")) {
+            _ if permanent => None,
+            (Some(d), f) => {
+                Some(OffsetDateTime::now_utc() + Duration::from_secs(d * f.unwrap_or(86400)))
+            }
+            (None, _) => old_expiry,
+        };
+        if matches!(self, Self::Scrim) && new_expiry.is_none() {
             command
                 .create_interaction_response(&http, |resp| {
                     resp.interaction_response_data(|data| {
-                        data.content(format!("Could not ban {}: {}", user.tag(), e))
+                        data.content("Scrim bans cannot be permanent")
                             .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
                     })
                 })
                 .await?;
-        } else if command.channel_id != CONFIG.support_bans {
-            command
-                .create_interaction_response(&http, |resp| {
-                    resp.interaction_response_data(|data| {
-                        match (result.as_ref(), db_result.as_ref()) {
-                            (Ok(_), Err(e)) => {
-                                embed.description(format_db_error(e));
-                                data.add_embed(embed.clone())
-                            }
-                            _ => data.add_embed(embed.clone()),
-                        }
-                    })
-                })
-                .await?;
+            return Ok(());
         }
 
-        result?;
+        let case = crate::consts::DATABASE
+            .fetch_punishments_for(id.0)
+            .into_iter()
+            .filter(|x| x.kind == self.punishment_kind())
+            .max_by_key(|x| x.id);
+        let old_reason = case.as_ref().map(|x| x.reason.clone());
+        let new_reason = opts.get_str("reason").or_else(|| old_reason.clone());
+
+        let db_result = match (self, new_expiry) {
+            (Self::Server, Some(date)) if old_expiry.is_some() => {
+                crate::consts::DATABASE.modify_unban_date("ScheduledUnbans", id.0, date)
+            }
+            (Self::Server, Some(date)) => crate::consts::DATABASE.add_unban(id.0, date),
+            (Self::Server, None) => crate::consts::DATABASE.remove_entry("ScheduledUnbans", id.0),
+            (Self::Scrim, date) => crate::consts::DATABASE.modify_unban_date(
+                "ScheduledScrimUnbans",
+                id.0,
+                // NOTE: Permanent scrim bans were rejected above
+                date.unwrap(),
+            ),
+            (Self::Kick | Self::SoftBan, _) => Ok(()),
+        };
+        db_result?;
+        match (self, new_expiry) {
+            (Self::Server, Some(date)) => {
+                crate::scheduler::schedule(JobKind::Unban, id.0, "", date)
+            }
+            (Self::Server, None) => crate::scheduler::cancel(JobKind::Unban, id.0, ""),
+            (Self::Scrim, Some(date)) => {
+                crate::scheduler::schedule(JobKind::ScrimUnban, id.0, "", date)
+            }
+            _ => {}
+        }
+        if let Some(case) = &case {
+            crate::consts::DATABASE.update_punishment(
+                case.id,
+                new_reason.as_deref().unwrap_or_default(),
+                new_expiry,
+            )?;
+        }
 
+        let format_expiry = |expiry: Option<OffsetDateTime>| {
+            expiry.map_or_else(
+                || "never".to_string(),
+                |x| format!("<t:{}:R>", x.unix_timestamp()),
+            )
+        };
+        let mut embed = CreateEmbed::default();
+        embed
+            .title(format!(
+                "{}'s {} has been edited",
+                user.tag(),
+                self.punishment_kind()
+            ))
+            .field("User", format!("<@{}>", id), false);
+        if let Some(case) = &case {
+            embed.field("Case", format!("#{}", case.id), false);
+        }
+        if new_reason != old_reason {
+            embed.field(
+                "Reason",
+                format!(
+                    "`{}` → `{}`",
+                    old_reason.unwrap_or_default(),
+                    new_reason.unwrap_or_default()
+                ),
+                false,
+            );
+        }
+        if new_expiry != old_expiry {
+            embed.field(
+                "Expires",
+                format!(
+                    "{} → {}",
+                    format_expiry(old_expiry),
+                    format_expiry(new_expiry)
+                ),
+                false,
+            );
+        }
+        embed
+            .field("Editor", format!("<@{}>", command.user.id), false)
+            .color(Color::ORANGE);
+
+        CONFIG
+            .support_bans
+            .send_message(&http, |msg| msg.set_embed(embed.clone()))
+            .await?;
+        command
+            .create_interaction_response(&http, |resp| {
+                resp.interaction_response_data(|data| data.add_embed(embed))
+            })
+            .await?;
         Ok(())
     }
 }
@@ -230,9 +621,6 @@ impl Command for Ban {
     fn name(&self) -> String {
         String::from("ban")
     }
-    async fn init(&self, ctx: &Context) {
-        tokio::spawn(update_loop(ctx.http.clone()));
-    }
     async fn register(&self, ctx: &Context) -> crate::Result<()> {
         let command = CONFIG.guild
             .create_application_command(&ctx, |c| {
@@ -240,39 +628,91 @@ impl Command for Ban {
                     .name(self.name())
                     .description("Bans the given user from the server. This is not meant for screenshare bans.")
                     .default_permission(false)
-                    .create_option(|o| {
-                        o.name("user")
-                            .description("The user to ban")
-                            .required(true)
-                            .kind(ApplicationCommandOptionType::User)
+                    .create_option(|add| {
+                        add.name("add")
+                            .description("Bans the given user from the server.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|o| {
+                                o.name("user")
+                                    .description("The user to ban")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::User)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("reason")
+                                    .description("Reason for the ban")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::String)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("duration")
+                                    .description("The ban duration. Default: forever")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::Integer)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("type")
+                                    .description("The ban duration type. Default: Days")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::Integer)
+                                    .add_int_choice("Seconds", 1)
+                                    .add_int_choice("Minutes", 60)
+                                    .add_int_choice("Hours", 60 * 60)
+                                    .add_int_choice("Days", 60 * 60 * 24)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("dmd")
+                                    .description("Should the last 7d of messages be removed?")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::Boolean)
+                            })
                     })
-                    .create_option(|o| {
-                        o.name("reason")
-                            .description("Reason for the ban")
-                            .required(true)
-                            .kind(ApplicationCommandOptionType::String)
-                    })
-                    .create_option(|o| {
-                        o.name("duration")
-                            .description("The ban duration. Default: forever")
-                            .required(false)
-                            .kind(ApplicationCommandOptionType::Integer)
-                    })
-                    .create_option(|o| {
-                        o.name("type")
-                            .description("The ban duration type. Default: Days")
-                            .required(false)
-                            .kind(ApplicationCommandOptionType::Integer)
-                            .add_int_choice("Seconds", 1)
-                            .add_int_choice("Minutes", 60)
-                            .add_int_choice("Hours", 60 * 60)
-                            .add_int_choice("Days", 60 * 60 * 24)
-                    })
-                    .create_option(|o| {
-                        o.name("dmd")
-                            .description("Should the last 7d of messages be removed?")
-                            .required(false)
-                            .kind(ApplicationCommandOptionType::Boolean)
+                    .create_option(|edit| {
+                        edit.name("edit")
+                            .description("Changes the reason or duration of a server ban or scrim ban.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|o| {
+                                o.name("user")
+                                    .description("The banned user")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::User)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("ban")
+                                    .description("The ban to edit")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::String)
+                                    .add_string_choice("Server ban", "server")
+                                    .add_string_choice("Scrim ban", "scrim")
+                            })
+                            .create_sub_option(|o| {
+                                o.name("reason")
+                                    .description("The new reason for the ban")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::String)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("duration")
+                                    .description("The new ban duration, counted from now")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::Integer)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("type")
+                                    .description("The ban duration type. Default: Days")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::Integer)
+                                    .add_int_choice("Seconds", 1)
+                                    .add_int_choice("Minutes", 60)
+                                    .add_int_choice("Hours", 60 * 60)
+                                    .add_int_choice("Days", 60 * 60 * 24)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("permanent")
+                                    .description("Make the ban permanent. Only server bans can be permanent")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::Boolean)
+                            })
                     })
             })
         .await?;
@@ -299,7 +739,15 @@ impl Command for Ban {
         ctx: &Context,
         command: &ApplicationCommandInteraction,
     ) -> crate::Result<()> {
-        BanType::Server.exec(&ctx.http, &ctx.cache, command).await?;
+        let cmd = &command.data.options[0];
+        match cmd.name.as_str() {
+            "add" => BanType::Server.exec(ctx, command, cmd).await?,
+            "edit" => match cmd.get_str("ban").as_deref() {
+                Some("scrim") => BanType::Scrim.edit(ctx, command, cmd).await?,
+                _ => BanType::Server.edit(ctx, command, cmd).await?,
+            },
+            _ => {}
+        }
 
         Ok(())
     }
@@ -309,36 +757,12 @@ impl Command for Ban {
     }
 }
 
-async fn update_loop(ctx: Arc<Http>) {
-    let database = &crate::consts::DATABASE;
-
-    loop {
-        let unbans = database.fetch_unbans();
-        let now = OffsetDateTime::now_utc();
-
-        for unban in unbans {
-            if unban.date < now {
-                let _ = CONFIG
-                    .guild
-                    .unban(&ctx, unban.id)
-                    .await
-                    .map(|_| database.remove_entry("ScheduledUnbans", unban.id));
-            }
-        }
-
-        tokio::time::sleep(Duration::from_secs(5 * 60)).await;
-    }
-}
-
 pub struct ScrimBan;
 #[async_trait]
 impl Command for ScrimBan {
     fn name(&self) -> String {
         String::from("scrimban")
     }
-    async fn init(&self, ctx: &Context) {
-        tokio::spawn(scrim_update_loop(ctx.http.clone()));
-    }
     async fn register(&self, ctx: &Context) -> crate::Result<()> {
         let command = CONFIG
             .guild
@@ -404,7 +828,7 @@ impl Command for ScrimBan {
         ctx: &Context,
         command: &ApplicationCommandInteraction,
     ) -> crate::Result<()> {
-        BanType::Scrim.exec(&ctx.http, &ctx.cache, command).await?;
+        BanType::Scrim.exec(ctx, command, command).await?;
 
         Ok(())
     }
@@ -414,34 +838,123 @@ impl Command for ScrimBan {
     }
 }
 
-async fn scrim_update_loop(ctx: Arc<Http>) {
-    let database = &crate::consts::DATABASE;
+pub struct Kick;
+#[async_trait]
+impl Command for Kick {
+    fn name(&self) -> String {
+        String::from("kick")
+    }
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Kicks the given user from the server.")
+                    .default_permission(false)
+                    .create_option(|o| {
+                        o.name("user")
+                            .description("The user to kick")
+                            .required(true)
+                            .kind(ApplicationCommandOptionType::User)
+                    })
+                    .create_option(|o| {
+                        o.name("reason")
+                            .description("Reason for the kick")
+                            .required(true)
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |c| {
+                c.create_permission(|p| {
+                    p.kind(ApplicationCommandPermissionType::Role)
+                        .id(CONFIG.support.0)
+                        .permission(true)
+                })
+                .create_permission(|p| {
+                    p.kind(ApplicationCommandPermissionType::Role)
+                        .id(CONFIG.staff.0)
+                        .permission(true)
+                })
+            })
+            .await?;
+        Ok(())
+    }
 
-    loop {
-        let unbans = database.fetch_scrim_unbans();
-        let now = OffsetDateTime::now_utc();
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        BanType::Kick.exec(ctx, command, command).await?;
 
-        for unban in unbans {
-            if unban.date > now {
-                continue;
-            }
-            let member = CONFIG.guild.member(&ctx, unban.id).await;
-            if let Ok(member) = member {
-                let res = UnbanType::Scrim
-                    .unban(
-                        &ctx,
-                        None,
-                        UnbanEntry::Scrim(unban),
-                        "Ban Expired".to_string(),
-                    )
-                    .await;
-                if let Err(err) = res {
-                    tracing::error!("Failed to unban {} upon expiration: {}", member, err);
-                }
-            }
-        }
+        Ok(())
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
+
+pub struct SoftBan;
+#[async_trait]
+impl Command for SoftBan {
+    fn name(&self) -> String {
+        String::from("softban")
+    }
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Bans and unbans the given user, removing their messages of the last 7 days.")
+                    .default_permission(false)
+                    .create_option(|o| {
+                        o.name("user")
+                            .description("The user to softban")
+                            .required(true)
+                            .kind(ApplicationCommandOptionType::User)
+                    })
+                    .create_option(|o| {
+                        o.name("reason")
+                            .description("Reason for the softban")
+                            .required(true)
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |c| {
+                c.create_permission(|p| {
+                    p.kind(ApplicationCommandPermissionType::Role)
+                        .id(CONFIG.support.0)
+                        .permission(true)
+                })
+                .create_permission(|p| {
+                    p.kind(ApplicationCommandPermissionType::Role)
+                        .id(CONFIG.staff.0)
+                        .permission(true)
+                })
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        BanType::SoftBan.exec(ctx, command, command).await?;
 
-        tokio::time::sleep(Duration::from_secs(5 * 60)).await;
+        Ok(())
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
     }
 }
 
@@ -0,0 +1,136 @@
+use bridge_scrims::interact_opts::InteractOpts;
+use serenity::{
+    async_trait,
+    builder::CreateEmbed,
+    client::Context,
+    model::interactions::{
+        application_command::{
+            ApplicationCommandInteraction, ApplicationCommandOptionType,
+            ApplicationCommandPermissionType,
+        },
+        InteractionApplicationCommandCallbackDataFlags,
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use crate::consts::CONFIG;
+use crate::db::{Punishment, PunishmentKind};
+
+use super::Command;
+
+/// Records a punishment, returning its case number. Database errors are logged rather than
+/// returned, as the punishment itself has already been applied at this point.
+pub fn record(
+    kind: PunishmentKind,
+    user: u64,
+    staff: Option<u64>,
+    reason: &str,
+    expires_at: Option<OffsetDateTime>,
+) -> Option<i64> {
+    match crate::consts::DATABASE.add_punishment(kind, user, staff, reason, expires_at) {
+        Ok(case) => Some(case),
+        Err(e) => {
+            tracing::error!("Could not record {} of {}: {}", kind, user, e);
+            None
+        }
+    }
+}
+
+pub fn case_embed(punishment: &Punishment) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!("Case #{}: {}", punishment.id, punishment.kind))
+        .field("User", format!("<@{}>", punishment.user), false)
+        .field(
+            "Staff",
+            punishment
+                .staff
+                .map_or_else(|| "Automatic".to_string(), |x| format!("<@{}>", x)),
+            false,
+        )
+        .field("Reason", format!("`{}`", punishment.reason), false)
+        .field(
+            "Date",
+            format!("<t:{}>", punishment.created_at.unix_timestamp()),
+            false,
+        )
+        .color(Color::BLURPLE);
+    if let Some(expires_at) = punishment.expires_at {
+        embed.field(
+            "Expires",
+            format!("<t:{}:R>", expires_at.unix_timestamp()),
+            false,
+        );
+    }
+    embed
+}
+
+pub struct Case;
+
+#[async_trait]
+impl Command for Case {
+    fn name(&self) -> String {
+        String::from("case")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx.http, |cmd| {
+                cmd.name(self.name())
+                    .description("Shows a moderation case")
+                    .create_option(|opt| {
+                        opt.name("id")
+                            .description("The case number")
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .required(true)
+                    })
+                    .default_permission(false)
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx.http, command.id, |p| {
+                for role in &[
+                    CONFIG.support,
+                    CONFIG.trial_support,
+                    CONFIG.ss_support,
+                    CONFIG.staff,
+                ] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        let id = command.get_u64("id").unwrap();
+        let punishment = crate::consts::DATABASE.fetch_punishment(id);
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.interaction_response_data(|data| match punishment {
+                    Some(ref punishment) => data.add_embed(case_embed(punishment)),
+                    None => data
+                        .content(format!("Case #{} does not exist.", id))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL),
+                })
+            })
+            .await?;
+        Ok(())
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
@@ -1,21 +1,38 @@
+use std::fmt::Write;
+use std::time::Duration;
+
 use serenity::{
     async_trait,
+    builder::{CreateComponents, CreateSelectMenuOptions},
     client::Context,
     futures::StreamExt,
     http::AttachmentType,
     model::{
+        channel::Message,
+        guild::Member,
         id::{ChannelId, UserId},
         interactions::{
-            application_command::ApplicationCommandInteraction,
-            message_component::MessageComponentInteraction,
-            InteractionApplicationCommandCallbackDataFlags,
+            application_command::{ApplicationCommandInteraction, ApplicationCommandOptionType},
+            message_component::{ButtonStyle, MessageComponentInteraction},
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
         },
     },
+    utils::Color,
 };
+use time::OffsetDateTime;
 
-use bridge_scrims::print_embeds::FormatEmbed;
+use bridge_scrims::{
+    interact_opts::InteractOpts,
+    print_embeds::FormatEmbed,
+    transcript::{Names, Transcript},
+};
 
+use super::ban::BanType;
 use super::{Button, Command};
+use crate::consts::{CONFIG, DATABASE};
+use crate::db::{
+    ArchivedTranscript, JobKind, PunishmentKind, ScheduledJob, ScreenshareOutcome, SupportTicket,
+};
 
 pub struct Close;
 
@@ -30,7 +47,32 @@ impl Command for Close {
             .create_application_command(&ctx.http, |command| {
                 command
                     .name(self.name())
-                    .description("Closes a screenshare")
+                    .description("Asks the creator to close the ticket, or closes it")
+                    .create_option(|option| {
+                        option
+                            .name("outcome")
+                            .description("How the screenshare ended. Required for screenshares")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::String);
+                        for outcome in ScreenshareOutcome::ALL {
+                            option.add_string_choice(outcome, outcome.as_str());
+                        }
+                        option
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("scrimban")
+                            .description("Also scrimban the user in question. Only for admitted, refused and cheating found")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Boolean)
+                    })
+                    .create_option(|option| {
+                        option
+                            .name("force")
+                            .description("Close right away instead of asking the creator of the ticket")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Boolean)
+                    })
             })
             .await?;
         Ok(())
@@ -41,17 +83,93 @@ impl Command for Close {
         command: &ApplicationCommandInteraction,
     ) -> crate::Result<()> {
         let channel = command.channel_id;
-        let close = close_ticket(ctx, command.user.id, channel).await?;
-        if !close {
+        let ticket = DATABASE.fetch_ticket(channel.0);
+        let screenshare = DATABASE.fetch_screenshares_for(channel.0);
+        let outcome = command
+            .get_str("outcome")
+            .map(|x| x.parse::<ScreenshareOutcome>())
+            .transpose()?;
+        let scrimban = command.get_bool("scrimban").unwrap_or(false);
+        let force = command.get_bool("force").unwrap_or(false);
+        let creator = match (&screenshare, &ticket) {
+            (Some(screenshare), _) => screenshare.creator,
+            (None, Some(ticket)) => ticket.creator,
+            (None, None) => return respond(ctx, command, "This is not a ticket!").await,
+        };
+        if !is_staff(command.member.as_ref(), ticket.as_ref()) {
+            return respond(ctx, command, NOT_STAFF).await;
+        }
+        // Support tickets have no outcome
+        let outcome = screenshare.as_ref().and(outcome);
+        let error = match outcome {
+            None if screenshare.is_some() => Some("Choose the outcome of the screenshare."),
+            None => None,
+            Some(outcome) => check_scrimban(outcome, scrimban, command.member.as_ref()),
+        };
+        if let Some(error) = error {
+            return respond(ctx, command, error).await;
+        }
+        let value = close_value(outcome, scrimban);
+
+        // Staff ask the creator to close the ticket, unless they force it or are the creator
+        if force || creator == command.user.id.0 {
             command
                 .create_interaction_response(&ctx.http, |resp| {
                     resp.interaction_response_data(|data| {
-                        data.content("This is not a screenshare ticket!")
+                        data.content("Are you sure you want to close this ticket?")
+                            .components(|c| confirm_components(c, &value))
                             .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
                     })
                 })
                 .await?;
+            return Ok(());
         }
+        if pending_request(channel).is_some() {
+            return respond(
+                ctx,
+                command,
+                "There already is a close request in this ticket.",
+            )
+            .await;
+        }
+        let deadline = OffsetDateTime::now_utc() + CLOSE_REQUEST_TIMEOUT;
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.interaction_response_data(|data| {
+                    data.content(format!("<@{}>", creator))
+                        .create_embed(|e| {
+                            e.title("Close request")
+                                .description(format!(
+                                    "<@{}> wants to close this ticket{}. Accept if everything is done, or reject and tell us what you still need. The ticket closes <t:{}:R> if nobody answers.",
+                                    command.user.id,
+                                    outcome.map(|x| format!(" as {}", x)).unwrap_or_default(),
+                                    deadline.unix_timestamp()
+                                ))
+                                .color(Color::BLURPLE)
+                        })
+                        .components(|c| {
+                            c.create_action_row(|row| {
+                                row.create_button(|b| {
+                                    b.label("Accept")
+                                        .style(ButtonStyle::Success)
+                                        .custom_id("close:request:accept")
+                                })
+                                .create_button(|b| {
+                                    b.label("Reject")
+                                        .style(ButtonStyle::Danger)
+                                        .custom_id("close:request:reject")
+                                })
+                            })
+                        })
+                })
+            })
+            .await?;
+        crate::scheduler::schedule(
+            JobKind::CloseRequest,
+            channel.0,
+            &format!("{}:{}", command.user.id, value),
+            deadline,
+        );
         Ok(())
     }
     fn new() -> Box<Self> {
@@ -61,61 +179,510 @@ impl Command for Close {
 
 #[async_trait]
 impl Button for Close {
+    fn prefix(&self) -> String {
+        self.name()
+    }
     async fn click(
         &self,
         ctx: &Context,
         command: &MessageComponentInteraction,
     ) -> crate::Result<()> {
-        close_ticket(ctx, command.user.id, command.channel_id).await?;
+        let channel = command.channel_id;
+        let mut parts = command.data.custom_id.splitn(3, ':').skip(1);
+        let step = parts.next();
+        // The creator can only answer close requests
+        let ticket = DATABASE.fetch_ticket(channel.0);
+        if step != Some("request") && !is_staff(command.member.as_ref(), ticket.as_ref()) {
+            command
+                .create_interaction_response(&ctx.http, |resp| {
+                    resp.interaction_response_data(|data| {
+                        data.content(NOT_STAFF)
+                            .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+        match step {
+            // The close button of screenshares asks for the outcome first
+            None if DATABASE.fetch_screenshares_for(channel.0).is_some() => {
+                command
+                    .create_interaction_response(&ctx.http, |resp| {
+                        resp.interaction_response_data(|data| {
+                            data.content("How did the screenshare end?")
+                                .components(|c| {
+                                    c.create_action_row(|row| {
+                                        row.create_select_menu(|menu| {
+                                            menu.custom_id("close:outcome")
+                                                .placeholder("Outcome")
+                                                .options(outcome_options)
+                                        })
+                                    })
+                                })
+                                .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                        })
+                    })
+                    .await?;
+            }
+            None => {
+                command
+                    .create_interaction_response(&ctx.http, |resp| {
+                        resp.interaction_response_data(|data| {
+                            data.content("Are you sure you want to close this ticket?")
+                                .components(|c| confirm_components(c, ""))
+                                .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                        })
+                    })
+                    .await?;
+            }
+            Some("outcome") => {
+                let value = command.data.values.first().cloned().unwrap_or_default();
+                let (outcome, scrimban) = parse_close_value(&value)?;
+                let error =
+                    outcome.and_then(|x| check_scrimban(x, scrimban, command.member.as_ref()));
+                command
+                    .create_interaction_response(&ctx.http, |resp| {
+                        resp.kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|data| match error {
+                                Some(error) => data.content(error).components(|c| c),
+                                None => data
+                                    .content(format!(
+                                        "Are you sure you want to close this screenshare as {}{}?",
+                                        outcome.map(|x| x.to_string()).unwrap_or_default(),
+                                        if scrimban {
+                                            " and scrimban the user in question"
+                                        } else {
+                                            ""
+                                        }
+                                    ))
+                                    .components(|c| confirm_components(c, &value)),
+                            })
+                    })
+                    .await?;
+            }
+            Some("confirm") => {
+                let (outcome, scrimban) = parse_close_value(parts.next().unwrap_or_default())?;
+                let error =
+                    outcome.and_then(|x| check_scrimban(x, scrimban, command.member.as_ref()));
+                command
+                    .create_interaction_response(&ctx.http, |resp| {
+                        resp.kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|data| {
+                                data.content(error.unwrap_or("Closing the ticket..."))
+                                    .components(|c| c)
+                            })
+                    })
+                    .await?;
+                if error.is_none() {
+                    close_with(ctx, command.user.id, channel, outcome, scrimban, None).await?;
+                }
+            }
+            Some("request") => answer_request(ctx, command, parts.next() == Some("accept")).await?,
+            Some(_) => {}
+        }
         Ok(())
     }
 }
 
-pub async fn close_ticket(
+/// Accepts or rejects the close request of the ticket, only its creator may answer
+async fn answer_request(
+    ctx: &Context,
+    command: &MessageComponentInteraction,
+    accept: bool,
+) -> crate::Result<()> {
+    let channel = command.channel_id;
+    let creator = DATABASE
+        .fetch_screenshares_for(channel.0)
+        .map(|x| x.creator)
+        .or_else(|| DATABASE.fetch_ticket(channel.0).map(|x| x.creator));
+    let request = match pending_request(channel) {
+        Some(request) if creator == Some(command.user.id.0) => Ok(request),
+        Some(_) => Err("Only the creator of the ticket can answer the close request."),
+        None => Err("This close request was already answered."),
+    };
+    let request = match request {
+        Ok(request) => request,
+        Err(error) => {
+            command
+                .create_interaction_response(&ctx.http, |resp| {
+                    resp.interaction_response_data(|data| {
+                        data.content(error)
+                            .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+    };
+    DATABASE.remove_entry("ScheduledJobs", request.id)?;
+    command
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|data| {
+                    data.content(format!(
+                        "<@{}> {} the close request.",
+                        command.user.id,
+                        if accept { "accepted" } else { "rejected" }
+                    ))
+                    .components(|c| c)
+                })
+        })
+        .await?;
+    if accept {
+        close_requested(ctx, channel, &request.data, "Close request accepted").await?;
+    }
+    Ok(())
+}
+
+/// The close request of the ticket that was not answered yet
+fn pending_request(channel: ChannelId) -> Option<ScheduledJob> {
+    DATABASE
+        .fetch_jobs()
+        .into_iter()
+        .find(|x| x.kind == JobKind::CloseRequest && x.target == channel.0)
+}
+
+/// Closes the ticket as it was requested. `data` is the data of the `CloseRequest` job.
+pub async fn close_requested(
+    ctx: &Context,
+    channel: ChannelId,
+    data: &str,
+    reason: &str,
+) -> crate::Result<()> {
+    let (requester, value) = data.split_once(':').unwrap_or((data, ""));
+    let (outcome, scrimban) = parse_close_value(value)?;
+    close_with(
+        ctx,
+        UserId(requester.parse()?),
+        channel,
+        outcome,
+        scrimban,
+        Some(reason),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Whether the member handles tickets like this one
+fn is_staff(member: Option<&Member>, ticket: Option<&SupportTicket>) -> bool {
+    let roles = match member {
+        Some(member) => &member.roles,
+        None => return false,
+    };
+    let ticket_roles = ticket
+        .and_then(|ticket| CONFIG.tickets.iter().find(|x| x.name == ticket.kind))
+        .map_or(&[][..], |x| &x.staff_roles[..]);
+    [CONFIG.ss_support, CONFIG.support, CONFIG.staff]
+        .iter()
+        .chain(ticket_roles)
+        .any(|x| roles.contains(x))
+}
+
+/// The outcome of a close as it is stored in select menus, buttons and jobs: empty for support
+/// tickets, else the outcome followed by `:scrimban` if the user in question is scrimbanned
+fn close_value(outcome: Option<ScreenshareOutcome>, scrimban: bool) -> String {
+    match outcome {
+        Some(outcome) if scrimban => format!("{}:scrimban", outcome.as_str()),
+        Some(outcome) => outcome.as_str().to_string(),
+        None => String::new(),
+    }
+}
+
+fn parse_close_value(value: &str) -> crate::Result<(Option<ScreenshareOutcome>, bool)> {
+    if value.is_empty() {
+        return Ok((None, false));
+    }
+    Ok(match value.split_once(':') {
+        Some((outcome, _)) => (Some(outcome.parse()?), true),
+        None => (Some(value.parse()?), false),
+    })
+}
+
+/// A button that closes the ticket right away
+fn confirm_components<'a>(
+    components: &'a mut CreateComponents,
+    value: &str,
+) -> &'a mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.label("Close")
+                .style(ButtonStyle::Danger)
+                .custom_id(format!("close:confirm:{}", value))
+        })
+    })
+}
+
+async fn respond(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    content: &str,
+) -> crate::Result<()> {
+    command
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.interaction_response_data(|data| {
+                data.content(content)
+                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+/// Every outcome, followed by the outcomes that can also scrimban the user in question
+fn outcome_options(options: &mut CreateSelectMenuOptions) -> &mut CreateSelectMenuOptions {
+    for outcome in ScreenshareOutcome::ALL {
+        options.create_option(|o| o.label(outcome).value(close_value(Some(outcome), false)));
+    }
+    for outcome in ScreenshareOutcome::ALL.iter().filter(|x| x.is_bannable()) {
+        options.create_option(|o| {
+            o.label(format!("{} + scrimban", outcome))
+                .value(close_value(Some(*outcome), true))
+        });
+    }
+    options
+}
+
+/// Returns why the requested scrimban can not be applied, if it can not
+fn check_scrimban(
+    outcome: ScreenshareOutcome,
+    scrimban: bool,
+    member: Option<&Member>,
+) -> Option<&'static str> {
+    if !scrimban {
+        return None;
+    }
+    if !outcome.is_bannable() {
+        return Some("Only admitted, refused and cheating found screenshares can scrimban.");
+    }
+    let roles = &member?.roles;
+    if [CONFIG.ss_support, CONFIG.support, CONFIG.staff]
+        .iter()
+        .any(|x| roles.contains(x))
+    {
+        None
+    } else {
+        Some("You do not have permission to scrimban.")
+    }
+}
+
+/// Closes the ticket, then scrimbans the user in question with the duration configured for the
+/// outcome if asked to
+async fn close_with(
     ctx: &Context,
     closer: UserId,
     channel: ChannelId,
+    outcome: Option<ScreenshareOutcome>,
+    scrimban: bool,
+    reason: Option<&str>,
+) -> crate::Result<bool> {
+    let screenshare = DATABASE.fetch_screenshares_for(channel.0);
+    if !close_ticket(ctx, Some(closer), channel, outcome, reason).await? {
+        return Ok(false);
+    }
+    let (screenshare, outcome) = match (screenshare, outcome) {
+        (Some(screenshare), Some(outcome)) if scrimban => (screenshare, outcome),
+        _ => return Ok(true),
+    };
+    let bans = &CONFIG.screenshare_bans;
+    let duration = match outcome {
+        ScreenshareOutcome::Admitted => bans.admitted,
+        ScreenshareOutcome::Refused => bans.refused,
+        _ => bans.cheating,
+    }
+    .map(Duration::from_secs)
+    .or_else(|| BanType::Scrim.default_duration());
+    let user = UserId(screenshare.in_question).to_user(&ctx.http).await?;
+    let ban = BanType::Scrim
+        .apply(
+            &ctx.http,
+            &ctx.cache,
+            &user,
+            Some(closer),
+            format!("Screenshare: {}", outcome),
+            duration.map(|x| OffsetDateTime::now_utc() + x),
+            0,
+        )
+        .await;
+    ban.result?;
+    tokio::spawn(crate::escalation::check(
+        ctx.clone(),
+        user.id,
+        PunishmentKind::ScrimBan,
+    ));
+    Ok(true)
+}
+
+/// Renders the messages of a channel, newest first as they are fetched, into an HTML transcript
+pub async fn render_transcript(ctx: &Context, title: String, mut messages: Vec<Message>) -> String {
+    messages.reverse();
+    let mut names = Names::default();
+    if let Some(guild) = CONFIG.guild.to_guild_cached(&ctx.cache).await {
+        names.roles = guild
+            .roles
+            .into_iter()
+            .map(|(id, x)| (id, x.name))
+            .collect();
+        names.channels = guild
+            .channels
+            .into_iter()
+            .map(|(id, x)| (id, x.name))
+            .collect();
+    }
+    Transcript {
+        title,
+        messages: &messages,
+        names,
+    }
+    .to_string()
+}
+
+/// Shown to everyone but staff that tries to close a ticket
+const NOT_STAFF: &str =
+    "Only staff can close tickets. The creator of a ticket can answer close requests.";
+
+/// How long the creator of a ticket has to answer a close request
+const CLOSE_REQUEST_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Closes a screenshare or support ticket: the transcript is logged and archived and the channel
+/// is deleted. `outcome` is only used for screenshares, `closer` is `None` for automatic closes.
+pub async fn close_ticket(
+    ctx: &Context,
+    closer: Option<UserId>,
+    channel: ChannelId,
+    outcome: Option<ScreenshareOutcome>,
+    reason: Option<&str>,
 ) -> crate::Result<bool> {
-    let screenshare = crate::consts::DATABASE.fetch_screenshares_for(channel.0);
-    if screenshare.is_none() {
+    let screenshare = DATABASE.fetch_screenshares_for(channel.0);
+    let ticket = DATABASE.fetch_ticket(channel.0);
+    if screenshare.is_none() && ticket.is_none() {
         return Ok(false);
     }
     let mut messages = Vec::new();
-    let raw_messages: Vec<_> = channel.messages_iter(&ctx.http).collect().await;
+    let raw_messages: Vec<_> = channel
+        .messages_iter(&ctx.http)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
 
-    for message in raw_messages.into_iter().flatten() {
+    for message in &raw_messages {
         messages.push(format!(
             "[{}] {}: {}",
             message.timestamp.format("%c"),
             message.author.tag(),
             message.content_safe(&ctx.cache).await
         ));
-        for embed in message.embeds {
-            messages.push(format!("Embed:\n{}", FormatEmbed(embed.into())));
+        for embed in &message.embeds {
+            messages.push(format!("Embed:\n{}", FormatEmbed(embed.clone().into())));
         }
     }
 
     messages.reverse();
-    let history = messages.join("\n").into_bytes().into();
-    let screenshare = screenshare.unwrap();
-    crate::CONFIG
-        .ss_logs
-        .send_message(&ctx.http, |msg| {
-            msg.files([AttachmentType::Bytes {
-                data: history,
-                filename: String::from("messages.txt"),
-            }])
-            .embed(|embed| {
-                embed.title("Screenshare closed").description(format!(
-                    "- Creator: <@{}>
+    let history = messages.join("\n");
+    let closed_by = closer.map_or_else(|| String::from("Automatic"), |x| format!("<@{}>", x));
+    let (log, title, mut description, involved) = match (&screenshare, &ticket) {
+        (Some(screenshare), _) => (
+            CONFIG.ss_logs,
+            String::from("Screenshare closed"),
+            format!(
+                "- Creator: <@{}>
 - In Question: <@{}>
-- Closer: <@{}>",
-                    screenshare.creator, screenshare.in_question, closer
-                ))
-            })
+- Claimer: {}
+- Closer: {}
+- Outcome: {}",
+                screenshare.creator,
+                screenshare.in_question,
+                screenshare
+                    .claimer
+                    .map_or_else(|| String::from("Nobody"), |x| format!("<@{}>", x)),
+                closed_by,
+                outcome.map_or_else(|| String::from("None"), |x| x.to_string())
+            ),
+            [
+                Some(screenshare.creator),
+                Some(screenshare.in_question),
+                screenshare.claimer,
+            ],
+        ),
+        (None, Some(ticket)) => {
+            let kind = CONFIG.tickets.iter().find(|x| x.name == ticket.kind);
+            (
+                kind.and_then(|x| x.logs)
+                    .unwrap_or_else(|| CONFIG.mod_logs.unwrap_or(CONFIG.support_bans)),
+                format!(
+                    "{} ticket closed",
+                    kind.map_or(ticket.kind.as_str(), |x| x.label.as_str())
+                ),
+                format!("- Creator: <@{}>\n- Closer: {}", ticket.creator, closed_by),
+                [Some(ticket.creator), None, None],
+            )
+        }
+        (None, None) => unreachable!(),
+    };
+    if let Some(reason) = reason {
+        write!(description, "\n- Reason: {}", reason)?;
+    }
+    let mut participants: Vec<_> = raw_messages
+        .iter()
+        .map(|x| x.author.id.0)
+        .chain(involved.into_iter().flatten())
+        .collect();
+    participants.sort_unstable();
+    participants.dedup();
+    let name = channel
+        .name(&ctx.cache)
+        .await
+        .unwrap_or_else(|| channel.to_string());
+    let transcript = render_transcript(ctx, name.clone(), raw_messages).await;
+    let archived = ArchivedTranscript {
+        channel: channel.0,
+        title: name,
+        participants,
+        outcome: screenshare.as_ref().and(outcome),
+        closed_at: OffsetDateTime::now_utc(),
+    };
+    if let Err(e) = super::transcripts::archive(&archived, &transcript, &history) {
+        tracing::error!("Could not archive the transcript of {}: {}", channel, e);
+    }
+    let upload = log
+        .send_message(&ctx.http, |msg| {
+            msg.files([
+                AttachmentType::Bytes {
+                    data: history.into_bytes().into(),
+                    filename: String::from("messages.txt"),
+                },
+                AttachmentType::Bytes {
+                    data: transcript.into_bytes().into(),
+                    filename: String::from("transcript.html"),
+                },
+            ])
+            .embed(|embed| embed.title(&title).description(&description))
         })
-        .await?;
-    crate::consts::DATABASE.remove_entry("Screenshares", channel.0)?;
+        .await;
+    // The ticket is closed either way, large transcripts may be over the attachment size limit
+    if let Err(e) = upload {
+        tracing::error!("Could not upload the transcript of {}: {}", channel, e);
+        let retry = log
+            .send_message(&ctx.http, |msg| {
+                msg.embed(|embed| {
+                    embed.title(&title).description(format!(
+                        "{}\n- Transcript: could not be uploaded, see `/transcripts`",
+                        description
+                    ))
+                })
+            })
+            .await;
+        if let Err(e) = retry {
+            tracing::error!("Could not log the close of {}: {}", channel, e);
+        }
+    }
+    if screenshare.is_some() {
+        DATABASE.close_screenshare(channel.0, closer.map(|x| x.0), outcome)?;
+    } else {
+        DATABASE.close_support_ticket(channel.0, closer.map(|x| x.0))?;
+    }
+    DATABASE.set_kept_open(channel.0, false)?;
     channel.delete(&ctx.http).await?;
     Ok(true)
 }
@@ -13,6 +13,7 @@ use serenity::{
         },
     },
 };
+use std::time::Duration;
 use time::OffsetDateTime;
 
 use bridge_scrims::interact_opts::InteractOpts;
@@ -26,6 +27,9 @@ enum Status {
     Ignored,
 }
 
+/// How long after a freeze staff are reminded that the user is still frozen
+const UNFREEZE_REMINDER: Duration = Duration::from_secs(30 * 60);
+
 pub struct Freeze;
 
 #[async_trait]
@@ -92,6 +96,9 @@ impl Command for Freeze {
 
 #[async_trait]
 impl Button for Freeze {
+    fn prefix(&self) -> String {
+        self.name()
+    }
     async fn click(
         &self,
         ctx: &Context,
@@ -121,6 +128,19 @@ impl Button for Freeze {
                 .await?;
             return Ok(());
         }
+        // The freeze button of the ticket message can only be used once
+        let claimable = crate::consts::DATABASE
+            .fetch_screenshares_for(command.channel_id.0)
+            .is_some_and(|x| x.claimer.is_none());
+        command
+            .message
+            .clone()
+            .edit(&ctx, |m| {
+                m.components(|comp| {
+                    super::screenshare::ticket_buttons(comp, user, claimable, false)
+                })
+            })
+            .await?;
         let status = freeze_user(ctx, user, command.user.id, command.channel_id).await?;
         let tag = user.to_user(&ctx.http).await?.tag();
         if let Status::Success = status {
@@ -220,6 +240,25 @@ async fn freeze_user(
         removed_roles.into(),
         OffsetDateTime::now_utc(),
     )?;
+    // A frozen user keeps the screenshare open
+    if let Some(screenshare) = crate::consts::DATABASE.fetch_screenshares_for(channel.0) {
+        if screenshare.in_question == user.id.0 {
+            crate::consts::DATABASE.clear_screenshare_deadline(screenshare.id)?;
+        }
+    }
+    crate::scheduler::schedule(
+        crate::db::JobKind::UnfreezeReminder,
+        user.id.0,
+        &channel.to_string(),
+        OffsetDateTime::now_utc() + UNFREEZE_REMINDER,
+    );
+    super::case::record(
+        crate::db::PunishmentKind::Freeze,
+        user.id.0,
+        Some(staff.0),
+        &format!("Frozen in <#{}>", channel),
+        None,
+    );
     crate::CONFIG
         .frozen_chat
         .send_message(&ctx.http, |msg| {
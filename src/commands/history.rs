@@ -0,0 +1,302 @@
+use std::fmt::Write;
+
+use bridge_scrims::interact_opts::InteractOpts;
+use serenity::{
+    async_trait,
+    builder::{CreateComponents, CreateEmbed},
+    client::Context,
+    model::{
+        id::UserId,
+        interactions::{
+            application_command::{
+                ApplicationCommandInteraction, ApplicationCommandOptionType,
+                ApplicationCommandPermissionType,
+            },
+            message_component::{ButtonStyle, MessageComponentInteraction},
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+        },
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use crate::consts::CONFIG;
+
+use super::{Button, Command};
+
+const PAGE_SIZE: usize = 10;
+
+struct Entry {
+    date: OffsetDateTime,
+    title: String,
+    text: String,
+}
+
+pub struct History;
+
+#[async_trait]
+impl Command for History {
+    fn name(&self) -> String {
+        String::from("history")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx.http, |cmd| {
+                cmd.name(self.name())
+                    .description("Shows the moderation history of a user")
+                    .create_option(|opt| {
+                        opt.name("user")
+                            .description("The user who's history to show")
+                            .kind(ApplicationCommandOptionType::User)
+                            .required(true)
+                    })
+                    .default_permission(false)
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx.http, command.id, |p| {
+                for role in &[
+                    CONFIG.support,
+                    CONFIG.trial_support,
+                    CONFIG.ss_support,
+                    CONFIG.staff,
+                ] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+        let user = UserId(command.get_str("user").unwrap().parse()?);
+        let (embed, components) = history_page(ctx, user, 0).await?;
+        command
+            .edit_original_interaction_response(&ctx.http, |r| {
+                r.add_embed(embed).components(|c| {
+                    *c = components;
+                    c
+                })
+            })
+            .await?;
+        Ok(())
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
+
+#[async_trait]
+impl Button for History {
+    fn prefix(&self) -> String {
+        self.name()
+    }
+    /// The page buttons have the custom id `history:<user>:<page>`
+    async fn click(
+        &self,
+        ctx: &Context,
+        command: &MessageComponentInteraction,
+    ) -> crate::Result<()> {
+        let parts: Vec<&str> = command.data.custom_id.split(':').collect();
+        let (user, page) = match parts.as_slice() {
+            [_, user, page] => (UserId(user.parse()?), page.parse()?),
+            _ => return Ok(()),
+        };
+        let allowed = command.member.as_ref().is_some_and(|member| {
+            [
+                CONFIG.support,
+                CONFIG.trial_support,
+                CONFIG.ss_support,
+                CONFIG.staff,
+            ]
+            .iter()
+            .any(|x| member.roles.contains(x))
+        });
+        if !allowed {
+            command
+                .create_interaction_response(&ctx.http, |resp| {
+                    resp.interaction_response_data(|data| {
+                        data.content("You are not allowed to view the history.")
+                            .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+        let (embed, components) = history_page(ctx, user, page).await?;
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|data| {
+                        data.add_embed(embed).set_components(components)
+                    })
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+/// The embed and page buttons of a page of the history of a user
+async fn history_page(
+    ctx: &Context,
+    user: UserId,
+    page: usize,
+) -> crate::Result<(CreateEmbed, CreateComponents)> {
+    let tag = user.to_user(&ctx.http).await?.tag();
+    let summary = status_summary(ctx, user).await;
+    let entries = collect_entries(user.0);
+    let pages = entries.len().max(1).div_ceil(PAGE_SIZE);
+    let page = page.min(pages - 1);
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!("History of {}", tag))
+        .description(&summary)
+        .footer(|f| f.text(format!("Page {} of {}", page + 1, pages)))
+        .color(Color::BLURPLE);
+    for entry in entries.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE) {
+        embed.field(
+            &entry.title,
+            format!("<t:{}>: {}", entry.date.unix_timestamp(), entry.text),
+            false,
+        );
+    }
+    let mut components = CreateComponents::default();
+    if pages > 1 {
+        components.create_action_row(|row| {
+            row.create_button(|b| {
+                b.label("Previous")
+                    .style(ButtonStyle::Secondary)
+                    .custom_id(format!("history:{}:{}", user, page.saturating_sub(1)))
+                    .disabled(page == 0)
+            })
+            .create_button(|b| {
+                b.label("Next")
+                    .style(ButtonStyle::Secondary)
+                    .custom_id(format!("history:{}:{}", user, page + 1))
+                    .disabled(page + 1 == pages)
+            })
+        });
+    }
+    Ok((embed, components))
+}
+
+/// Describes the punishments that are currently in effect for the user
+async fn status_summary(ctx: &Context, user: UserId) -> String {
+    let mut summary = String::new();
+    let server_ban = crate::consts::DATABASE
+        .fetch_unbans()
+        .into_iter()
+        .find(|x| x.id == user.0);
+    if let Some(ban) = server_ban {
+        let _ = writeln!(
+            summary,
+            "- Server banned until <t:{}:R>",
+            ban.date.unix_timestamp()
+        );
+    } else if let Ok(bans) = CONFIG.guild.bans(&ctx.http).await {
+        if bans.iter().any(|x| x.user.id == user) {
+            let _ = writeln!(summary, "- Permanently server banned");
+        }
+    }
+    let scrim_ban = crate::consts::DATABASE
+        .fetch_scrim_unbans()
+        .into_iter()
+        .find(|x| x.id == user.0);
+    if let Some(ban) = scrim_ban {
+        let _ = writeln!(
+            summary,
+            "- Scrim banned until <t:{}:R>",
+            ban.date.unix_timestamp()
+        );
+    }
+    if let Some(freeze) = crate::consts::DATABASE.fetch_freezes_for(user.0) {
+        let _ = writeln!(
+            summary,
+            "- Frozen since <t:{}:R>",
+            freeze.time.unix_timestamp()
+        );
+    }
+    let warnings = crate::consts::DATABASE
+        .fetch_warnings_for(user.0)
+        .iter()
+        .filter(|x| x.is_active())
+        .count();
+    if warnings > 0 {
+        let _ = writeln!(summary, "- {} active warnings", warnings);
+    }
+    if summary.is_empty() {
+        summary.push_str("No active punishments.");
+    }
+    summary
+}
+
+/// Collects the notes, cases and screenshares of a user, newest first
+fn collect_entries(user: u64) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for note in crate::consts::DATABASE.fetch_notes_for(user) {
+        entries.push(Entry {
+            date: note.created_at,
+            title: format!("Note {}", note.id),
+            text: format!("`{}` by <@!{}>", note.note, note.creator),
+        });
+    }
+    for punishment in crate::consts::DATABASE.fetch_punishments_for(user) {
+        let staff = punishment
+            .staff
+            .map_or_else(|| "automatically".to_string(), |x| format!("by <@!{}>", x));
+        let until = punishment
+            .expires_at
+            .map(|x| format!(", until <t:{}>", x.unix_timestamp()))
+            .unwrap_or_default();
+        entries.push(Entry {
+            date: punishment.created_at,
+            title: format!("Case #{}: {}", punishment.id, punishment.kind),
+            text: format!("`{}` {}{}", punishment.reason, staff, until),
+        });
+    }
+    for screenshare in crate::consts::DATABASE.fetch_screenshare_history_for(user) {
+        let role = if screenshare.in_question == user {
+            format!("Requested by <@!{}>", screenshare.creator)
+        } else {
+            format!("Requested on <@!{}>", screenshare.in_question)
+        };
+        let closer = screenshare.closer.map_or_else(
+            || String::from("automatically"),
+            |x| format!("by <@!{}>", x),
+        );
+        let status = match (screenshare.closed_at, screenshare.outcome) {
+            (Some(_), Some(outcome)) => format!(", closed {} as {}", closer, outcome),
+            (Some(_), None) => format!(", closed {}", closer),
+            (None, _) => String::from(", still open"),
+        };
+        entries.push(Entry {
+            date: screenshare
+                .created_at
+                .or(screenshare.closed_at)
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+            title: String::from("Screenshare"),
+            text: format!("{}{}", role, status),
+        });
+    }
+    entries.sort_by_key(|x| std::cmp::Reverse(x.date));
+    entries
+}
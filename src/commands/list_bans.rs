@@ -26,14 +26,17 @@ impl Command for ListBans {
             .guild
             .create_application_command(&ctx.http, |cmd| {
                 cmd.name(self.name())
-                    .description("List all of the (scrim)bans")
+                    .description("List all of the (scrim)bans and timeouts")
                     .create_option(|opt| {
                         opt.name("type")
-                            .description("Wether you want to list scrimbans or server bans")
+                            .description(
+                                "Wether you want to list scrimbans, server bans or timeouts",
+                            )
                             .required(true)
                             .kind(ApplicationCommandOptionType::String)
                             .add_string_choice("Scrim", "sc")
                             .add_string_choice("Server", "sv")
+                            .add_string_choice("Timeout", "to")
                     })
                     .default_permission(false)
             })
@@ -89,6 +92,19 @@ impl Command for ListBans {
                 }
                 result
             }
+            "to" => {
+                let timeouts = crate::consts::DATABASE.fetch_timeouts();
+                let mut result = String::new();
+                for timeout in timeouts {
+                    writeln!(
+                        result,
+                        "- <@!{}>: timed out until <t:{}:R>",
+                        timeout.id,
+                        timeout.date.unix_timestamp()
+                    )?;
+                }
+                result
+            }
             _ => {
                 return Ok(());
             }
@@ -98,14 +114,11 @@ impl Command for ListBans {
                 resp.interaction_response_data(|data| {
                     data.create_embed(|embed| {
                         embed
-                            .title(format!(
-                                "{} Bans",
-                                if operation.as_str() == "sv" {
-                                    "Server"
-                                } else {
-                                    "Scrim"
-                                }
-                            ))
+                            .title(match operation.as_str() {
+                                "sv" => "Server Bans",
+                                "sc" => "Scrim Bans",
+                                _ => "Timeouts",
+                            })
                             .description(desc)
                     })
                 })
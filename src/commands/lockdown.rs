@@ -0,0 +1,527 @@
+use std::time::Duration;
+
+use bridge_scrims::interact_opts::InteractOpts;
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommandOption, CreateEmbed},
+    client::Context,
+    http::Http,
+    model::{
+        channel::{ChannelType, GuildChannel, PermissionOverwrite, PermissionOverwriteType},
+        id::{ChannelId, RoleId},
+        interactions::{
+            application_command::{
+                ApplicationCommandInteraction, ApplicationCommandOptionType,
+                ApplicationCommandPermissionType,
+            },
+            InteractionResponseType,
+        },
+        Permissions,
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use super::Command;
+use crate::consts::{CONFIG, DATABASE};
+use crate::db::{ChannelChange, JobKind};
+
+fn target_options(o: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    o.create_sub_option(|o| {
+        o.name("channel")
+            .description("The channel or category. Default: this channel")
+            .required(false)
+            .kind(ApplicationCommandOptionType::Channel)
+            .channel_types(&[ChannelType::Text, ChannelType::Category])
+    })
+    .create_sub_option(|o| {
+        o.name("all")
+            .description("Change all channels from the config")
+            .required(false)
+            .kind(ApplicationCommandOptionType::Boolean)
+    })
+}
+
+fn revert_options(o: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    o.create_sub_option(|o| {
+        o.name("duration")
+            .description("Revert the change after this time. Default: never")
+            .required(false)
+            .kind(ApplicationCommandOptionType::Integer)
+    })
+    .create_sub_option(|o| {
+        o.name("type")
+            .description("The duration type. Default: Minutes")
+            .required(false)
+            .kind(ApplicationCommandOptionType::Integer)
+            .add_int_choice("Seconds", 1)
+            .add_int_choice("Minutes", 60)
+            .add_int_choice("Hours", 60 * 60)
+            .add_int_choice("Days", 60 * 60 * 24)
+    })
+    .create_sub_option(|o| {
+        o.name("reason")
+            .description("The reason for the change")
+            .required(false)
+            .kind(ApplicationCommandOptionType::String)
+    })
+}
+
+/// The setting a channel goes back to when no earlier setting was stored
+fn default_setting(change: ChannelChange) -> &'static str {
+    match change {
+        ChannelChange::Lockdown => "neutral",
+        ChannelChange::Slowmode => "0",
+    }
+}
+
+/// The current setting of the channel, in the format of `ChannelRevert::previous`
+fn current_setting(channel: &GuildChannel, change: ChannelChange) -> String {
+    match change {
+        ChannelChange::Lockdown => {
+            let everyone = PermissionOverwriteType::Role(RoleId(CONFIG.guild.0));
+            let overwrite = channel
+                .permission_overwrites
+                .iter()
+                .find(|x| x.kind == everyone);
+            match overwrite {
+                Some(x) if x.deny.contains(Permissions::SEND_MESSAGES) => "deny",
+                Some(x) if x.allow.contains(Permissions::SEND_MESSAGES) => "allow",
+                _ => "neutral",
+            }
+            .to_string()
+        }
+        #[allow(deprecated)]
+        ChannelChange::Slowmode => channel.slow_mode_rate.unwrap_or(0).to_string(),
+    }
+}
+
+async fn apply_setting(
+    http: &Http,
+    channel: &GuildChannel,
+    change: ChannelChange,
+    setting: &str,
+) -> serenity::Result<()> {
+    match change {
+        ChannelChange::Lockdown => {
+            let everyone = PermissionOverwriteType::Role(RoleId(CONFIG.guild.0));
+            let existing = channel
+                .permission_overwrites
+                .iter()
+                .find(|x| x.kind == everyone);
+            let (mut allow, mut deny) = existing
+                .map_or((Permissions::empty(), Permissions::empty()), |x| {
+                    (x.allow, x.deny)
+                });
+            allow.remove(Permissions::SEND_MESSAGES);
+            deny.remove(Permissions::SEND_MESSAGES);
+            match setting {
+                "allow" => allow.insert(Permissions::SEND_MESSAGES),
+                "deny" => deny.insert(Permissions::SEND_MESSAGES),
+                _ => {}
+            }
+            if allow.is_empty() && deny.is_empty() {
+                if existing.is_some() {
+                    channel.id.delete_permission(http, everyone).await?;
+                }
+                return Ok(());
+            }
+            channel
+                .id
+                .create_permission(
+                    http,
+                    &PermissionOverwrite {
+                        allow,
+                        deny,
+                        kind: everyone,
+                    },
+                )
+                .await
+        }
+        ChannelChange::Slowmode => {
+            let seconds = setting.parse().unwrap_or(0);
+            channel
+                .id
+                .edit(http, |c| c.rate_limit_per_user(seconds))
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/// Resolves the selected channel, category or config list to text channels
+async fn targets(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    opts: &(impl InteractOpts + Sync),
+) -> serenity::Result<Vec<GuildChannel>> {
+    let channels = CONFIG.guild.channels(&ctx.http).await?;
+    let selected = if opts.get_bool("all").unwrap_or(false) {
+        CONFIG.lockdown_channels.clone()
+    } else {
+        vec![opts
+            .get_str("channel")
+            .and_then(|x| x.parse().ok())
+            .map_or(command.channel_id, ChannelId)]
+    };
+    let mut result: Vec<GuildChannel> = Vec::new();
+    for id in selected {
+        match channels.get(&id) {
+            Some(channel) if channel.kind == ChannelType::Category => result.extend(
+                channels
+                    .values()
+                    .filter(|x| x.category_id == Some(id) && x.kind == ChannelType::Text)
+                    .cloned(),
+            ),
+            Some(channel) => result.push(channel.clone()),
+            None => {}
+        }
+    }
+    result.sort_by_key(|x| (x.position, x.id));
+    result.dedup_by_key(|x| x.id);
+    Ok(result)
+}
+
+fn mention_list(channels: &[String]) -> String {
+    let mut text = String::new();
+    for channel in channels {
+        if text.len() + channel.len() > 1000 {
+            text.push_str("...");
+            break;
+        }
+        text.push_str(channel);
+        text.push(' ');
+    }
+    text
+}
+
+/// Changes the targeted channels. A `setting` of `None` restores the setting from before the first
+/// change. With a duration the change is reverted automatically.
+async fn change_channels(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    opts: &(impl InteractOpts + Sync),
+    change: ChannelChange,
+    setting: Option<String>,
+    title: String,
+) -> crate::Result<()> {
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+        })
+        .await?;
+    let channels = targets(ctx, command, opts).await?;
+    if channels.is_empty() {
+        command
+            .edit_original_interaction_response(&ctx.http, |r| r.content("No channels found."))
+            .await?;
+        return Ok(());
+    }
+    let until = opts.get_u64("duration").map(|duration| {
+        OffsetDateTime::now_utc()
+            + Duration::from_secs(duration * opts.get_u64("type").unwrap_or(60))
+    });
+    let reason = opts
+        .get_str("reason")
+        .unwrap_or_else(|| String::from("No reason given."));
+
+    let mut changed = Vec::new();
+    let mut failed = Vec::new();
+    for channel in &channels {
+        let previous = current_setting(channel, change);
+        let stored = DATABASE.fetch_channel_revert(channel.id.0, change);
+        let setting = setting.clone().unwrap_or_else(|| {
+            stored.as_ref().map_or_else(
+                || default_setting(change).to_string(),
+                |x| x.previous.clone(),
+            )
+        });
+        if let Err(e) = apply_setting(&ctx.http, channel, change, &setting).await {
+            failed.push(format!("<#{}>: {}", channel.id, e));
+            continue;
+        }
+        // The original setting is stored when the first change starts and forgotten once the
+        // channel is back to it
+        let restored = stored
+            .as_ref()
+            .map_or(setting == previous, |x| x.previous == setting);
+        let db_result = if restored {
+            crate::scheduler::cancel(JobKind::ChannelRevert, channel.id.0, change.as_str());
+            DATABASE.remove_channel_revert(channel.id.0, change)
+        } else {
+            match until {
+                Some(until) => crate::scheduler::schedule(
+                    JobKind::ChannelRevert,
+                    channel.id.0,
+                    change.as_str(),
+                    until,
+                ),
+                None => {
+                    crate::scheduler::cancel(JobKind::ChannelRevert, channel.id.0, change.as_str())
+                }
+            }
+            DATABASE.add_channel_revert(channel.id.0, change, &previous, until)
+        };
+        if let Err(e) = db_result {
+            tracing::error!("Could not store the revert of {}: {}", channel.name, e);
+        }
+        changed.push(format!("<#{}>", channel.id));
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(title)
+        .field("Reason", format!("`{}`", reason), false)
+        .field("Staff", format!("<@{}>", command.user.id), false)
+        .color(Color::ORANGE);
+    if !changed.is_empty() {
+        embed.field("Channels", mention_list(&changed), false);
+    }
+    if !failed.is_empty() {
+        embed.field("Failed", mention_list(&failed), false);
+    }
+    if let Some(until) = until {
+        embed.field(
+            "Reverted",
+            format!("<t:{}:R>", until.unix_timestamp()),
+            false,
+        );
+    }
+    CONFIG
+        .mod_logs
+        .unwrap_or(CONFIG.support_bans)
+        .send_message(&ctx.http, |msg| msg.set_embed(embed.clone()))
+        .await?;
+    command
+        .edit_original_interaction_response(&ctx.http, |r| r.add_embed(embed))
+        .await?;
+    Ok(())
+}
+
+/// Restores the setting from before a scheduled change, run by the scheduler
+pub async fn revert(
+    http: &Http,
+    channel: ChannelId,
+    change: ChannelChange,
+) -> serenity::Result<()> {
+    let revert = match DATABASE.fetch_channel_revert(channel.0, change) {
+        Some(revert) if revert.date.is_some() => revert,
+        // The change was already reverted by hand or no longer ends automatically
+        _ => return Ok(()),
+    };
+    let channels = CONFIG.guild.channels(http).await?;
+    // The channel was deleted in the meantime
+    let guild_channel = match channels.get(&channel) {
+        Some(channel) => channel,
+        None => {
+            let _ = DATABASE.remove_channel_revert(channel.0, change);
+            return Ok(());
+        }
+    };
+    apply_setting(http, guild_channel, change, &revert.previous).await?;
+    let _ = DATABASE.remove_channel_revert(channel.0, change);
+    let title = match change {
+        ChannelChange::Lockdown => "Lockdown ended".to_string(),
+        ChannelChange::Slowmode => format!("Slowmode set to {}s", revert.previous),
+    };
+    CONFIG
+        .mod_logs
+        .unwrap_or(CONFIG.support_bans)
+        .send_message(http, |msg| {
+            msg.embed(|e| {
+                e.title(title)
+                    .field("Channels", format!("<#{}>", channel), false)
+                    .field("Reason", "`Scheduled revert`", false)
+                    .color(Color::DARK_GREEN)
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+pub struct Lockdown;
+
+#[async_trait]
+impl Command for Lockdown {
+    fn name(&self) -> String {
+        String::from("lockdown")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Stops everyone from sending messages in channels.")
+                    .default_permission(false)
+                    .create_option(|o| {
+                        o.name("start")
+                            .description("Locks the channels.")
+                            .kind(ApplicationCommandOptionType::SubCommand);
+                        target_options(o);
+                        revert_options(o)
+                    })
+                    .create_option(|o| {
+                        o.name("end")
+                            .description("Unlocks the channels.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|o| {
+                                o.name("reason")
+                                    .description("The reason for the change")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::String)
+                            });
+                        target_options(o)
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |p| {
+                for role in &[CONFIG.support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        let cmd = &command.data.options[0];
+        match cmd.name.as_str() {
+            "start" => {
+                change_channels(
+                    ctx,
+                    command,
+                    cmd,
+                    ChannelChange::Lockdown,
+                    Some("deny".to_string()),
+                    "Lockdown started".to_string(),
+                )
+                .await
+            }
+            "end" => {
+                change_channels(
+                    ctx,
+                    command,
+                    cmd,
+                    ChannelChange::Lockdown,
+                    None,
+                    "Lockdown ended".to_string(),
+                )
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
+
+pub struct Slowmode;
+
+#[async_trait]
+impl Command for Slowmode {
+    fn name(&self) -> String {
+        String::from("slowmode")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Sets the slowmode of channels.")
+                    .default_permission(false)
+                    .create_option(|o| {
+                        o.name("seconds")
+                            .description("Seconds between messages, 0 to disable")
+                            .required(true)
+                            .kind(ApplicationCommandOptionType::Integer)
+                    })
+                    .create_option(|o| {
+                        o.name("channel")
+                            .description("The channel or category. Default: this channel")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Text, ChannelType::Category])
+                    })
+                    .create_option(|o| {
+                        o.name("all")
+                            .description("Change all channels from the config")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Boolean)
+                    })
+                    .create_option(|o| {
+                        o.name("duration")
+                            .description("Revert the change after this time. Default: never")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Integer)
+                    })
+                    .create_option(|o| {
+                        o.name("type")
+                            .description("The duration type. Default: Minutes")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .add_int_choice("Seconds", 1)
+                            .add_int_choice("Minutes", 60)
+                            .add_int_choice("Hours", 60 * 60)
+                            .add_int_choice("Days", 60 * 60 * 24)
+                    })
+                    .create_option(|o| {
+                        o.name("reason")
+                            .description("The reason for the change")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |p| {
+                for role in &[CONFIG.support, CONFIG.trial_support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        // Discord allows at most 6 hours
+        let seconds = command.get_u64("seconds").unwrap_or(0).min(6 * 60 * 60);
+        change_channels(
+            ctx,
+            command,
+            command,
+            ChannelChange::Slowmode,
+            Some(seconds.to_string()),
+            format!("Slowmode set to {}s", seconds),
+        )
+        .await
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
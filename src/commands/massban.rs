@@ -0,0 +1,359 @@
+use std::time::Duration;
+
+use bridge_scrims::interact_opts::InteractOpts;
+use serenity::{
+    async_trait,
+    builder::{CreateApplicationCommand, CreateEmbed},
+    client::Context,
+    model::{
+        channel::MessageType,
+        id::{MessageId, UserId},
+        interactions::{
+            application_command::{
+                ApplicationCommandInteraction, ApplicationCommandOptionType,
+                ApplicationCommandPermissionType,
+            },
+            InteractionResponseType,
+        },
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use super::ban::{outranks, BanType};
+use super::Command;
+use crate::consts::CONFIG;
+
+/// How many users are banned between two progress updates
+const PROGRESS_INTERVAL: usize = 5;
+
+fn create_options<'a>(
+    cmd: &'a mut CreateApplicationCommand,
+    name: String,
+    description: &str,
+) -> &'a mut CreateApplicationCommand {
+    cmd.name(name)
+        .description(description)
+        .default_permission(false)
+        .create_option(|o| {
+            o.name("reason")
+                .description("Reason for the bans")
+                .required(true)
+                .kind(ApplicationCommandOptionType::String)
+        })
+        .create_option(|o| {
+            o.name("users")
+                .description("User IDs separated by spaces")
+                .required(false)
+                .kind(ApplicationCommandOptionType::String)
+        })
+        .create_option(|o| {
+            o.name("from")
+                .description("Ban everyone who joined from this welcome message on")
+                .required(false)
+                .kind(ApplicationCommandOptionType::String)
+        })
+        .create_option(|o| {
+            o.name("to")
+                .description("Ban everyone who joined until this welcome message")
+                .required(false)
+                .kind(ApplicationCommandOptionType::String)
+        })
+        .create_option(|o| {
+            o.name("duration")
+                .description("The ban duration. Default: the default of the ban")
+                .required(false)
+                .kind(ApplicationCommandOptionType::Integer)
+        })
+        .create_option(|o| {
+            o.name("type")
+                .description("The ban duration type. Default: Days")
+                .required(false)
+                .kind(ApplicationCommandOptionType::Integer)
+                .add_int_choice("Seconds", 1)
+                .add_int_choice("Minutes", 60)
+                .add_int_choice("Hours", 60 * 60)
+                .add_int_choice("Days", 60 * 60 * 24)
+        })
+}
+
+/// Collects the users that joined between two messages in the welcome channel, both inclusive
+async fn joined_between(
+    ctx: &Context,
+    from: MessageId,
+    to: MessageId,
+) -> crate::Result<Vec<UserId>> {
+    let channel = CONFIG
+        .guild
+        .to_guild_cached(&ctx.cache)
+        .await
+        .and_then(|x| x.system_channel_id)
+        .ok_or("The server has no welcome channel")?;
+    let mut users = Vec::new();
+    let mut after = MessageId(from.0.saturating_sub(1));
+    loop {
+        let messages = channel
+            .messages(&ctx.http, |r| r.after(after).limit(100))
+            .await?;
+        let last = match messages.iter().map(|x| x.id).max() {
+            Some(last) => last,
+            None => break,
+        };
+        let mut joins: Vec<_> = messages
+            .iter()
+            .filter(|x| x.kind == MessageType::MemberJoin && x.id <= to)
+            .collect();
+        joins.sort_by_key(|x| x.id);
+        users.extend(joins.iter().map(|x| x.author.id));
+        if last >= to {
+            break;
+        }
+        after = last;
+    }
+    Ok(users)
+}
+
+async fn mass_ban(
+    ban_type: BanType,
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+) -> crate::Result<()> {
+    command
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+        })
+        .await?;
+    let reason = command.get_str("reason").unwrap();
+    let duration = match (command.get_u64("duration"), command.get_u64("type")) {
+        (Some(d), f) => Some(Duration::from_secs(d * f.unwrap_or(86400))),
+        (None, _) => ban_type.default_duration(),
+    };
+    let unban_date = duration.map(|duration| OffsetDateTime::now_utc() + duration);
+
+    let mut ids: Vec<UserId> = command
+        .get_str("users")
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|x| {
+            x.trim_matches(|c| c == '<' || c == '@' || c == '!' || c == '>')
+                .parse()
+                .ok()
+        })
+        .map(UserId)
+        .collect();
+    match (command.get_str("from"), command.get_str("to")) {
+        (Some(from), Some(to)) => {
+            let from = MessageId(from.parse()?);
+            let to = MessageId(to.parse()?);
+            if from > to {
+                command
+                    .edit_original_interaction_response(&ctx.http, |r| {
+                        r.content("`from` has to be sent before `to`.")
+                    })
+                    .await?;
+                return Ok(());
+            }
+            ids.extend(joined_between(ctx, from, to).await?);
+        }
+        (None, None) => {}
+        _ => {
+            command
+                .edit_original_interaction_response(&ctx.http, |r| {
+                    r.content("Both `from` and `to` are needed to ban by welcome messages.")
+                })
+                .await?;
+            return Ok(());
+        }
+    }
+    ids.sort();
+    ids.dedup();
+    if ids.is_empty() {
+        command
+            .edit_original_interaction_response(&ctx.http, |r| r.content("No users to ban."))
+            .await?;
+        return Ok(());
+    }
+
+    let staff = command.member.clone().unwrap();
+    let mut banned = Vec::new();
+    let mut failed = Vec::new();
+    for (i, id) in ids.iter().enumerate() {
+        if i % PROGRESS_INTERVAL == 0 {
+            command
+                .edit_original_interaction_response(&ctx.http, |r| {
+                    r.content(format!("Banning... {}/{}", i, ids.len()))
+                })
+                .await?;
+        }
+        let user = match id.to_user(&ctx.http).await {
+            Ok(user) => user,
+            Err(e) => {
+                failed.push(format!("<@{}>: {}", id, e));
+                continue;
+            }
+        };
+        // Users that already left can still be server banned
+        if let Ok(member) = CONFIG.guild.member(&ctx.http, id).await {
+            if !outranks(&ctx.cache, &staff, &member).await {
+                failed.push(format!("<@{}>: no permission", id));
+                continue;
+            }
+        }
+        let outcome = ban_type
+            .apply(
+                &ctx.http,
+                &ctx.cache,
+                &user,
+                Some(command.user.id),
+                reason.clone(),
+                unban_date,
+                0,
+            )
+            .await;
+        match outcome.result {
+            Ok(()) => {
+                banned.push(format!("<@{}>", id));
+                tokio::spawn(crate::escalation::check(
+                    ctx.clone(),
+                    *id,
+                    ban_type.punishment_kind(),
+                ));
+            }
+            Err(e) => failed.push(format!("<@{}>: {}", id, e)),
+        }
+    }
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!("Mass {}", ban_type.punishment_kind()))
+        .field("Reason", format!("`{}`", reason), false)
+        .field("Staff", format!("<@{}>", command.user.id), false)
+        .color(if failed.is_empty() {
+            Color::DARK_GREEN
+        } else {
+            Color::ORANGE
+        });
+    if !banned.is_empty() {
+        embed.field(format!("Banned ({})", banned.len()), list(&banned), false);
+    }
+    if !failed.is_empty() {
+        embed.field(format!("Failed ({})", failed.len()), list(&failed), false);
+    }
+    command
+        .edit_original_interaction_response(&ctx.http, |r| {
+            r.content(format!("Done! {}/{}", ids.len(), ids.len()))
+                .add_embed(embed.clone())
+        })
+        .await?;
+    CONFIG
+        .support_bans
+        .send_message(&ctx.http, |m| m.set_embed(embed))
+        .await?;
+    Ok(())
+}
+
+/// Joins the entries into an embed field value, cutting it off before the field limit
+pub fn list(entries: &[String]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        if text.len() + entry.len() > 1000 {
+            text.push_str("...");
+            break;
+        }
+        text.push_str(entry);
+        text.push('\n');
+    }
+    text
+}
+
+pub struct MassBan;
+
+#[async_trait]
+impl Command for MassBan {
+    fn name(&self) -> String {
+        String::from("massban")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                create_options(
+                    c,
+                    self.name(),
+                    "Bans several users from the server at once.",
+                )
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |p| {
+                for role in &[CONFIG.support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        mass_ban(BanType::Server, ctx, command).await
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
+
+pub struct MassScrimBan;
+
+#[async_trait]
+impl Command for MassScrimBan {
+    fn name(&self) -> String {
+        String::from("massscrimban")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                create_options(c, self.name(), "Screenshare-bans several users at once.")
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |p| {
+                for role in &[CONFIG.ss_support, CONFIG.support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        mass_ban(BanType::Scrim, ctx, command).await
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
@@ -3,23 +3,32 @@ use serenity::client::Context;
 use serenity::model::interactions::message_component::MessageComponentInteraction;
 use serenity::model::prelude::application_command::ApplicationCommandInteraction;
 pub mod ban;
+pub mod case;
 pub mod close;
 pub mod council;
 pub mod freeze;
+pub mod history;
 pub mod list_bans;
+pub mod lockdown;
+pub mod massban;
+pub mod modstats;
 pub mod notes;
 pub mod ping;
 pub mod prefabs;
 pub mod purge;
+pub mod raid;
 pub mod reaction;
 pub mod reload;
 pub mod roll;
 pub mod screenshare;
 pub mod screensharers;
+pub mod temprole;
 pub mod ticket;
 pub mod timeout;
+pub mod transcripts;
 pub mod unban;
 pub mod unfreeze;
+pub mod warn;
 
 #[async_trait]
 pub trait Command: Send + Sync {
@@ -41,8 +50,15 @@ pub trait Command: Send + Sync {
         Self: Sized;
 }
 
+/// Handles message components (buttons and select menus). Components are routed to the
+/// implementor by the part of their `custom_id` before the first `:`, so they keep working after
+/// the command that sent them returned or the bot restarted.
+///
+/// Modals are not handled: serenity 0.10 has no modal interactions, so routing modal submits has
+/// to wait for a serenity upgrade.
 #[async_trait]
-pub trait Button: Command {
+pub trait Button: Send + Sync {
+    fn prefix(&self) -> String;
     async fn click(
         &self,
         ctx: &Context,
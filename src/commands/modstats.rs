@@ -0,0 +1,226 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use bridge_scrims::interact_opts::InteractOpts;
+use futures::future::join_all;
+use serenity::{
+    async_trait,
+    client::Context,
+    http::AttachmentType,
+    model::{
+        id::UserId,
+        interactions::{
+            application_command::{
+                ApplicationCommandInteraction, ApplicationCommandOptionType,
+                ApplicationCommandPermissionType,
+            },
+            InteractionResponseType,
+        },
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use super::Command;
+use crate::consts::{CONFIG, DATABASE};
+
+/// How many staff members are shown on the leaderboard
+const LEADERBOARD_SIZE: usize = 15;
+
+fn summary(stats: &crate::db::ModStats) -> String {
+    format!(
+        "{} bans, {} scrimbans, {} timeouts, {} warnings, {} notes, {} freezes, {} tickets closed, {} purges",
+        stats.bans,
+        stats.scrim_bans,
+        stats.timeouts,
+        stats.warnings,
+        stats.notes,
+        stats.freezes,
+        stats.tickets_closed,
+        stats.purges
+    )
+}
+
+async fn to_csv(ctx: &Context, stats: &[(u64, crate::db::ModStats)]) -> String {
+    let tags = join_all(stats.iter().map(|(id, _)| async move {
+        UserId(*id)
+            .to_user(&ctx)
+            .await
+            .map_or_else(|_| String::new(), |x| x.tag())
+    }))
+    .await;
+    let mut csv = String::from(
+        "id,tag,bans,scrimbans,timeouts,warnings,notes,freezes,tickets_closed,purges,total\n",
+    );
+    for ((id, stats), tag) in stats.iter().zip(tags) {
+        let _ = writeln!(
+            csv,
+            "{},\"{}\",{},{},{},{},{},{},{},{},{}",
+            id,
+            tag.replace('"', "\"\""),
+            stats.bans,
+            stats.scrim_bans,
+            stats.timeouts,
+            stats.warnings,
+            stats.notes,
+            stats.freezes,
+            stats.tickets_closed,
+            stats.purges,
+            stats.total()
+        );
+    }
+    csv
+}
+
+pub struct ModStats;
+
+#[async_trait]
+impl Command for ModStats {
+    fn name(&self) -> String {
+        String::from("modstats")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description(
+                        "Shows what staff members did. Without a user a leaderboard is shown.",
+                    )
+                    .default_permission(false)
+                    .create_option(|o| {
+                        o.name("user")
+                            .description("The staff member")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::User)
+                    })
+                    .create_option(|o| {
+                        o.name("period")
+                            .description("Only count actions from this period. Default: Month")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Integer)
+                            .add_int_choice("Day", 1)
+                            .add_int_choice("Week", 7)
+                            .add_int_choice("Month", 30)
+                            .add_int_choice("Quarter", 90)
+                            .add_int_choice("Year", 365)
+                            .add_int_choice("All time", 0)
+                    })
+                    .create_option(|o| {
+                        o.name("csv")
+                            .description("Attach the statistics as a CSV file")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::Boolean)
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |p| {
+                for role in &[CONFIG.support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        command
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+        let days = command.get_u64("period").unwrap_or(30);
+        let since = if days == 0 {
+            OffsetDateTime::UNIX_EPOCH
+        } else {
+            OffsetDateTime::now_utc() - Duration::from_secs(days * 24 * 60 * 60)
+        };
+        let period = match days {
+            0 => "all time".to_string(),
+            1 => "the last day".to_string(),
+            days => format!("the last {} days", days),
+        };
+        let user = command.get_str("user").and_then(|x| x.parse::<u64>().ok());
+
+        let mut stats: Vec<_> = DATABASE.fetch_mod_stats(since).into_iter().collect();
+        stats.sort_by_key(|(id, stats)| (std::cmp::Reverse(stats.total()), *id));
+        if let Some(user) = user {
+            stats.retain(|(id, _)| *id == user);
+            if stats.is_empty() {
+                stats.push((user, Default::default()));
+            }
+        }
+
+        command
+            .edit_original_interaction_response(&ctx.http, |r| {
+                r.create_embed(|e| {
+                    match user {
+                        Some(user) => {
+                            let stats = &stats[0].1;
+                            e.title("Moderator statistics")
+                                .description(format!("<@{}> in {}", user, period))
+                                .field("Bans", stats.bans, true)
+                                .field("Scrimbans", stats.scrim_bans, true)
+                                .field("Timeouts", stats.timeouts, true)
+                                .field("Warnings", stats.warnings, true)
+                                .field("Notes", stats.notes, true)
+                                .field("Freezes", stats.freezes, true)
+                                .field("Tickets closed", stats.tickets_closed, true)
+                                .field("Purges", stats.purges, true)
+                                .field("Total", stats.total(), true);
+                        }
+                        None => {
+                            let mut desc = String::new();
+                            for (i, (id, stats)) in stats.iter().take(LEADERBOARD_SIZE).enumerate()
+                            {
+                                let _ = writeln!(
+                                    desc,
+                                    "{}. <@{}>: **{}**\n{}",
+                                    i + 1,
+                                    id,
+                                    stats.total(),
+                                    summary(stats)
+                                );
+                            }
+                            if desc.is_empty() {
+                                desc = String::from("Nobody did anything.");
+                            }
+                            e.title(format!("Moderator leaderboard for {}", period))
+                                .description(desc);
+                        }
+                    }
+                    e.color(Color::BLURPLE)
+                })
+            })
+            .await?;
+
+        if command.get_bool("csv").unwrap_or(false) {
+            let csv = to_csv(ctx, &stats).await;
+            command
+                .create_followup_message(&ctx.http, |m| {
+                    m.add_file(AttachmentType::Bytes {
+                        data: csv.into_bytes().into(),
+                        filename: String::from("modstats.csv"),
+                    })
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
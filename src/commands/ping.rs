@@ -139,7 +139,7 @@ impl Command for Ping {
         self.cooldowns
             .add_user_cooldown_key(cid.clone(), Duration::from_secs(60 * 5), command.user.id)
             .await;
-        let text = command.get_str("text").unwrap_or_else(|| "".to_string());
+        let text = command.get_str("text").unwrap_or_default();
         command
             .channel_id
             .send_message(&ctx.http, |r| {
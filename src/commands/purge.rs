@@ -16,6 +16,7 @@ use bridge_scrims::interact_opts::InteractOpts;
 
 use crate::commands::Command;
 use crate::consts::CONFIG;
+use time::OffsetDateTime;
 
 pub enum PurgeOption {
     All,
@@ -171,6 +172,7 @@ impl Command for Purge {
 
         let option = self.options.get(&filter).unwrap();
         let mut i = 0;
+        let mut deleted = 0;
         while let Some(Ok(message)) = messages.next().await {
             i += 1;
 
@@ -180,9 +182,19 @@ impl Command for Purge {
 
             if option.check(command, message.clone()).await {
                 // ignore errors here since it doesn't matter if we can't delete
-                let _ = message.delete(&ctx.http).await;
+                if message.delete(&ctx.http).await.is_ok() {
+                    deleted += 1;
+                }
             }
         }
+        if let Err(e) = crate::consts::DATABASE.add_purge(
+            command.user.id.0,
+            channel.0,
+            deleted,
+            OffsetDateTime::now_utc(),
+        ) {
+            tracing::error!("Could not store purge: {}", e);
+        }
         command
             .edit_original_interaction_response(&ctx.http, |r| {
                 r.content("Purge Successful!".to_string())
@@ -0,0 +1,211 @@
+use crate::commands::{Button, Command};
+use bridge_scrims::interact_opts::InteractOpts;
+use serenity::{
+    async_trait,
+    builder::CreateEmbed,
+    model::{
+        id::UserId,
+        interactions::{
+            application_command::{
+                ApplicationCommandInteraction, ApplicationCommandOptionType,
+                ApplicationCommandPermissionType,
+            },
+            message_component::MessageComponentInteraction,
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+        },
+    },
+    prelude::Context,
+    utils::Color,
+};
+
+use super::ban::BanType;
+use super::massban::list;
+use crate::consts::CONFIG;
+
+pub struct Raid;
+
+#[async_trait]
+impl Command for Raid {
+    fn name(&self) -> String {
+        "raid".to_string()
+    }
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let cmd = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Deal with raids.")
+                    .create_option(|ban| {
+                        ban.kind(ApplicationCommandOptionType::SubCommand)
+                            .name("ban")
+                            .description("Ban everyone who joined during the last raid.")
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::String)
+                                    .name("reason")
+                                    .description("The reason for the bans.")
+                                    .required(false)
+                            })
+                    })
+                    .create_option(|end| {
+                        end.kind(ApplicationCommandOptionType::SubCommand)
+                            .name("end")
+                            .description("End raid mode.")
+                    })
+                    .default_permission(false)
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, cmd.id, |p| {
+                for role in &[CONFIG.support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+        let cmd = &command.data.options[0];
+        match cmd.name.as_str() {
+            "ban" => {
+                let reason = cmd
+                    .get_str("reason")
+                    .unwrap_or_else(|| String::from("Raid"));
+                let members = crate::consts::DATABASE.fetch_raid_members();
+                if members.is_empty() {
+                    command
+                        .edit_original_interaction_response(&ctx, |r| {
+                            r.content("No users joined during the last raid.")
+                        })
+                        .await?;
+                    return Ok(());
+                }
+                let mut banned = Vec::new();
+                let mut failed = Vec::new();
+                for id in members.iter().map(|x| UserId(*x)) {
+                    let user = match id.to_user(&ctx.http).await {
+                        Ok(user) => user,
+                        Err(e) => {
+                            failed.push(format!("<@{}>: {}", id, e));
+                            continue;
+                        }
+                    };
+                    let outcome = BanType::Server
+                        .apply(
+                            &ctx.http,
+                            &ctx.cache,
+                            &user,
+                            Some(command.user.id),
+                            reason.clone(),
+                            None,
+                            1,
+                        )
+                        .await;
+                    match outcome.result {
+                        Ok(()) => banned.push(format!("<@{}>", id)),
+                        Err(e) => {
+                            tracing::error!("Could not ban raid member {}: {}", id, e);
+                            failed.push(format!("<@{}>: {}", id, e));
+                        }
+                    }
+                }
+
+                let mut embed = CreateEmbed::default();
+                embed
+                    .title("Raid members banned")
+                    .field("Reason", format!("`{}`", reason), false)
+                    .field("Staff", format!("<@{}>", command.user.id), false)
+                    .color(Color::RED);
+                if !banned.is_empty() {
+                    embed.field(format!("Banned ({})", banned.len()), list(&banned), false);
+                }
+                if !failed.is_empty() {
+                    embed.field(format!("Failed ({})", failed.len()), list(&failed), false);
+                }
+                CONFIG
+                    .support_bans
+                    .send_message(&ctx.http, |msg| msg.set_embed(embed.clone()))
+                    .await?;
+                // The members are only forgotten once the bans are logged
+                crate::consts::DATABASE.clear_raid_members()?;
+                command
+                    .edit_original_interaction_response(&ctx, |r| r.add_embed(embed))
+                    .await?;
+            }
+            "end" => {
+                let ended = crate::raid::end(ctx).await;
+                command
+                    .edit_original_interaction_response(&ctx, |r| {
+                        r.content(if ended {
+                            "Raid mode has been ended."
+                        } else {
+                            "Raid mode is not active."
+                        })
+                    })
+                    .await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
+
+/// The "End raid mode" button of raid alerts
+#[async_trait]
+impl Button for Raid {
+    fn prefix(&self) -> String {
+        self.name()
+    }
+    async fn click(
+        &self,
+        ctx: &Context,
+        interaction: &MessageComponentInteraction,
+    ) -> crate::Result<()> {
+        let allowed = interaction.member.as_ref().is_some_and(|member| {
+            member.roles.contains(&CONFIG.support) || member.roles.contains(&CONFIG.staff)
+        });
+        if !allowed {
+            interaction
+                .create_interaction_response(&ctx.http, |resp| {
+                    resp.interaction_response_data(|data| {
+                        data.content("You are not allowed to end raid mode.")
+                            .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+        let ended = crate::raid::end(ctx).await;
+        interaction
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|data| {
+                        data.content(if ended {
+                            format!("Raid mode ended by <@{}>", interaction.user.id)
+                        } else {
+                            String::from("Raid mode was already ended")
+                        })
+                        .components(|c| c.set_action_rows(Default::default()))
+                    })
+            })
+            .await?;
+        Ok(())
+    }
+}
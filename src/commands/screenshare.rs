@@ -1,30 +1,37 @@
-use std::{fmt::Display, time::Duration};
+use std::time::Duration;
 
 use serenity::{
     async_trait,
-    builder::CreateMessage,
+    builder::{CreateComponents, CreateMessage},
     client::Context,
     model::{
         channel::{ChannelType, PermissionOverwrite, PermissionOverwriteType, ReactionType},
-        id::UserId,
+        id::{ChannelId, UserId},
         interactions::{
             application_command::{
-                ApplicationCommandInteraction as ACI, ApplicationCommandOptionType,
+                ApplicationCommandInteraction as ACI,
+                ApplicationCommandInteractionDataOption as ACIDO, ApplicationCommandOptionType,
             },
-            message_component::ButtonStyle,
+            message_component::{ButtonStyle, MessageComponentInteraction},
             InteractionApplicationCommandCallbackDataFlags,
         },
+        user::User,
         Permissions,
     },
 };
+use time::OffsetDateTime;
 
-use crate::commands::close::Close;
 use bridge_scrims::{
     hypixel::{Player, PlayerDataRequest},
     interact_opts::InteractOpts,
 };
 
-use super::{close, freeze::Freeze, Button, Command};
+use super::{Button, Command};
+use crate::consts::{CONFIG, DATABASE};
+use crate::db::JobKind;
+
+/// Tickets are closed if the user in question was not frozen within this time
+const FREEZE_DEADLINE: Duration = Duration::from_secs(60 * 15);
 
 lazy_static::lazy_static! {
     // allow:
@@ -35,34 +42,6 @@ lazy_static::lazy_static! {
 
 }
 
-#[derive(Clone, Copy)]
-pub enum Operation {
-    Close,
-    Freeze,
-}
-
-#[derive(Debug)]
-pub struct OperationDoesNotExist;
-
-impl std::error::Error for OperationDoesNotExist {}
-impl Display for OperationDoesNotExist {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Operation does not exist")
-    }
-}
-
-impl TryFrom<&'_ str> for Operation {
-    type Error = OperationDoesNotExist;
-
-    fn try_from(value: &'_ str) -> Result<Self, Self::Error> {
-        match value {
-            "close" => Ok(Self::Close),
-            "freeze" => Ok(Self::Freeze),
-            _ => Err(OperationDoesNotExist),
-        }
-    }
-}
-
 pub struct Screenshare;
 
 #[async_trait]
@@ -76,229 +55,437 @@ impl Command for Screenshare {
             .create_application_command(&ctx.http, |command| {
                 command
                     .name(self.name())
-                    .description("Creates a screenshare ticket")
-                    .create_option(|option| {
-                        option
-                            .name("player")
-                            .description("The person to request a screenshare to.")
-                            .required(true)
-                            .kind(ApplicationCommandOptionType::User)
+                    .description("Screenshare tickets")
+                    .create_option(|create| {
+                        create
+                            .name("create")
+                            .description("Creates a screenshare ticket")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("player")
+                                    .description("The person to request a screenshare to.")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::User)
+                            })
+                            .create_sub_option(|option| {
+                                option
+                                    .name("ign")
+                                    .description("The Minecraft ingame name of the person that you want to be screenshared.")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::String)
+                            })
                     })
-                    .create_option(|option| {
-                        option
-                            .name("ign")
-                            .description("The Minecraft ingame name of the person that you want to be screenshared.")
-                            .required(true)
-                            .kind(ApplicationCommandOptionType::String)
+                    .create_option(|unclaim| {
+                        unclaim
+                            .name("unclaim")
+                            .description("Gives up the screenshare ticket in this channel")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                    })
+                    .create_option(|transfer| {
+                        transfer
+                            .name("transfer")
+                            .description("Moves the screenshare ticket in this channel to another screensharer")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|option| {
+                                option
+                                    .name("screensharer")
+                                    .description("The screensharer that takes over the ticket")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::User)
+                            })
                     })
             })
             .await?;
         Ok(())
     }
     async fn run(&self, ctx: &Context, command: &ACI) -> crate::Result<()> {
-        let in_question = UserId(command.get_str("player").unwrap().parse()?);
-        let screenshare = crate::consts::DATABASE.fetch_screenshares_for(command.user.id.0);
-        if let Some(screenshare) = screenshare {
-            command
-                .create_interaction_response(&ctx.http, |msg| {
-                    msg.interaction_response_data(|data| {
-                        data.content(format!(
-                            "You already have an active screenshare in <#${}>",
-                            screenshare.id
-                        ))
+        let cmd = &command.data.options[0];
+        match cmd.name.as_str() {
+            "create" => create(ctx, command, cmd).await,
+            "unclaim" => unclaim(ctx, command).await,
+            "transfer" => transfer(ctx, command, cmd).await,
+            _ => Ok(()),
+        }
+    }
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
+
+#[async_trait]
+impl Button for Screenshare {
+    fn prefix(&self) -> String {
+        String::from("claim")
+    }
+
+    async fn click(
+        &self,
+        ctx: &Context,
+        command: &MessageComponentInteraction,
+    ) -> crate::Result<()> {
+        let screenshare = match DATABASE.fetch_screenshares_for(command.channel_id.0) {
+            Some(screenshare) => screenshare,
+            None => return Ok(()),
+        };
+        let (content, claimed) = if !command
+            .user
+            .has_role(&ctx.http, CONFIG.guild, CONFIG.ss_support)
+            .await?
+        {
+            (String::from("You are not a screensharer!"), false)
+        } else if let Some(claimer) = screenshare.claimer {
+            (
+                format!("This ticket was already claimed by <@{}>", claimer),
+                false,
+            )
+        } else if !DATABASE.claim_screenshare(screenshare.id, command.user.id.0)? {
+            // Someone else claimed it since the ticket was fetched
+            let claimer = DATABASE
+                .fetch_screenshares_for(screenshare.id)
+                .and_then(|x| x.claimer)
+                .map_or_else(|| String::from("someone else"), |x| format!("<@{}>", x));
+            (
+                format!("This ticket was just claimed by {}", claimer),
+                false,
+            )
+        } else {
+            (String::from("You claimed this ticket."), true)
+        };
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.interaction_response_data(|data| {
+                    data.content(content)
                         .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
-                    })
                 })
-                .await?;
+            })
+            .await?;
+        if !claimed {
             return Ok(());
         }
-
-        let result: Result<_, serenity::Error> = {
-            let channels = crate::CONFIG.guild.channels(&ctx.http).await?;
-            let category = channels
-                .iter()
-                .find(|ch| {
-                    ch.1.kind == ChannelType::Category
-                        && ch.0 == &crate::CONFIG.screenshare_requests
+        // The freeze button is gone once the user in question was frozen
+        let frozen = DATABASE
+            .fetch_freezes_for(screenshare.in_question)
+            .is_some();
+        command
+            .message
+            .clone()
+            .edit(&ctx, |m| {
+                m.components(|comp| {
+                    ticket_buttons(comp, UserId(screenshare.in_question), false, !frozen)
                 })
-                .ok_or(serenity::Error::Other("Channel does not exist."))?;
+            })
+            .await?;
+        command
+            .channel_id
+            .say(
+                &ctx.http,
+                format!("<@{}> claimed this ticket.", command.user.id),
+            )
+            .await?;
+        // Renames are limited to 2 per 10 minutes, so they may wait for a while
+        rename(ctx, command.channel_id, Some(&command.user)).await?;
+        Ok(())
+    }
+}
 
-            let mut count: Option<i64> = None;
-            crate::consts::DATABASE.count_rows("Screenshares", "", |val| {
-                if let sqlite::Value::Integer(co) = val[0] {
-                    count = Some(co);
-                }
+/// The buttons of the ticket message. The claim button is left out once the ticket is claimed,
+/// the freeze button once it was used.
+pub fn ticket_buttons(
+    components: &mut CreateComponents,
+    in_question: UserId,
+    claimable: bool,
+    freezable: bool,
+) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        if claimable {
+            row.create_button(|button| {
+                button
+                    .label("Claim")
+                    .style(ButtonStyle::Success)
+                    .emoji(ReactionType::Unicode(From::from("🙋")))
+                    .custom_id("claim")
+            });
+        }
+        if freezable {
+            row.create_button(|button| {
+                button
+                    .label("Freeze")
+                    .style(ButtonStyle::Primary)
+                    .emoji(ReactionType::Custom {
+                        animated: false,
+                        id: CONFIG.freeze_emoji,
+                        name: None,
+                    })
+                    .custom_id(format!("freeze:{}", in_question))
             });
+        }
+        row.create_button(|button| {
+            button
+                .label("Close")
+                .style(ButtonStyle::Danger)
+                .emoji(ReactionType::Unicode(From::from("⛔")))
+                .custom_id("close")
+        })
+    })
+}
+
+/// Renames the ticket to show who claimed it, e.g. `screenshare-12-name`
+async fn rename(ctx: &Context, channel: ChannelId, claimer: Option<&User>) -> crate::Result<()> {
+    let current = channel
+        .to_channel(&ctx.http)
+        .await?
+        .guild()
+        .ok_or("The ticket is not a server channel")?
+        .name;
+    let base = current.splitn(3, '-').take(2).collect::<Vec<_>>().join("-");
+    let name = match claimer {
+        Some(claimer) => format!("{}-{}", base, claimer.name),
+        None => base,
+    };
+    channel.edit(&ctx.http, |c| c.name(name)).await?;
+    Ok(())
+}
+
+/// Whether the user may unclaim or transfer the ticket
+fn can_reassign(command: &ACI, screenshare: &crate::db::Screenshare) -> bool {
+    let roles = &command.member.as_ref().unwrap().roles;
+    screenshare.claimer == Some(command.user.id.0)
+        || roles.contains(&CONFIG.support)
+        || roles.contains(&CONFIG.staff)
+}
+
+async fn respond(ctx: &Context, command: &ACI, content: impl ToString) -> crate::Result<()> {
+    command
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.interaction_response_data(|data| {
+                data.content(content)
+                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+            })
+        })
+        .await?;
+    Ok(())
+}
 
-            let new_channel = crate::CONFIG
-                .guild
-                .create_channel(&ctx.http, |ch| {
-                    ch.name(format!("screenshare-{}", count.unwrap_or_default() + 1))
-                        .category(category.0)
-                        .kind(ChannelType::Text)
-                        .permissions(
-                            // Iterator black magic
-                            std::iter::repeat((*ALLOW_PERMS, *DENY_PERMS))
-                                // Creator, Screensharers and in question
-                                .zip([
-                                    PermissionOverwriteType::Member(command.user.id),
-                                    PermissionOverwriteType::Member(in_question),
-                                    PermissionOverwriteType::Role(crate::CONFIG.ss_support),
-                                ])
-                                .map(|((allow, deny), kind)| PermissionOverwrite {
-                                    allow,
-                                    deny,
-                                    kind,
-                                })
-                                .chain(std::iter::once(PermissionOverwrite {
-                                    allow: Permissions::empty(),
-                                    deny: Permissions::READ_MESSAGES,
-                                    kind: PermissionOverwriteType::Role(
-                                        crate::CONFIG.guild.0.into(),
-                                    ),
-                                })),
-                        )
+async fn unclaim(ctx: &Context, command: &ACI) -> crate::Result<()> {
+    let screenshare = match DATABASE.fetch_screenshares_for(command.channel_id.0) {
+        Some(screenshare) => screenshare,
+        None => return respond(ctx, command, "This is not a screenshare ticket.").await,
+    };
+    if screenshare.claimer.is_none() {
+        return respond(ctx, command, "This ticket is not claimed.").await;
+    }
+    if !can_reassign(command, &screenshare) {
+        return respond(ctx, command, "You did not claim this ticket.").await;
+    }
+    DATABASE.set_screenshare_claimer(screenshare.id, None)?;
+    command
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.interaction_response_data(|data| {
+                data.content(format!(
+                    "<@&{}> This ticket was unclaimed and needs a screensharer.",
+                    CONFIG.ss_support
+                ))
+                .components(|comp| {
+                    comp.create_action_row(|row| {
+                        row.create_button(|button| {
+                            button
+                                .label("Claim")
+                                .style(ButtonStyle::Success)
+                                .emoji(ReactionType::Unicode(From::from("🙋")))
+                                .custom_id("claim")
+                        })
+                    })
                 })
-                .await?;
-            Ok(new_channel)
-        };
+            })
+        })
+        .await?;
+    rename(ctx, command.channel_id, None).await?;
+    Ok(())
+}
 
-        if let Ok(channel) = result {
-            let mut message = CreateMessage::default();
+async fn transfer(ctx: &Context, command: &ACI, opts: &ACIDO) -> crate::Result<()> {
+    let screenshare = match DATABASE.fetch_screenshares_for(command.channel_id.0) {
+        Some(screenshare) => screenshare,
+        None => return respond(ctx, command, "This is not a screenshare ticket.").await,
+    };
+    if !can_reassign(command, &screenshare) {
+        return respond(ctx, command, "You did not claim this ticket.").await;
+    }
+    let target = UserId(opts.get_str("screensharer").unwrap().parse()?)
+        .to_user(&ctx.http)
+        .await?;
+    if !target
+        .has_role(&ctx.http, CONFIG.guild, CONFIG.ss_support)
+        .await?
+    {
+        return respond(
+            ctx,
+            command,
+            format!("{} is not a screensharer.", target.tag()),
+        )
+        .await;
+    }
+    DATABASE.set_screenshare_claimer(screenshare.id, Some(target.id.0))?;
+    command
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.interaction_response_data(|data| {
+                data.content(format!(
+                    "<@{}> transferred this ticket to <@{}>.",
+                    command.user.id, target.id
+                ))
+            })
+        })
+        .await?;
+    rename(ctx, command.channel_id, Some(&target)).await?;
+    Ok(())
+}
 
-            let name = command.get_str("ign").unwrap();
-            let player = Player::fetch_from_username(name.clone()).await?;
-            let playerstats = PlayerDataRequest(crate::CONFIG.hypixel_token.clone(), player)
-                .send()
-                .await
-                .unwrap_or_default();
+async fn create(ctx: &Context, command: &ACI, opts: &ACIDO) -> crate::Result<()> {
+    let in_question = UserId(opts.get_str("player").unwrap().parse()?);
+    let screenshare = crate::consts::DATABASE.fetch_screenshares_for(command.user.id.0);
+    if let Some(screenshare) = screenshare {
+        command
+            .create_interaction_response(&ctx.http, |msg| {
+                msg.interaction_response_data(|data| {
+                    data.content(format!(
+                        "You already have an active screenshare in <#${}>",
+                        screenshare.id
+                    ))
+                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+            })
+            .await?;
+        return Ok(());
+    }
 
-            let db_result = crate::consts::DATABASE.add_screenshare(
-                channel.id.0,
-                command.user.id.0,
-                in_question.0,
-            );
-            if db_result.is_err() {
-                channel
-                    .send_message(
-                        &ctx.http,
-                       |x| x.content("An error occured in the database. The ticket may not work as expected."),
-                    )
-                    .await?;
+    let result: Result<_, serenity::Error> = {
+        let channels = crate::CONFIG.guild.channels(&ctx.http).await?;
+        let category = channels
+            .iter()
+            .find(|ch| {
+                ch.1.kind == ChannelType::Category && ch.0 == &crate::CONFIG.screenshare_requests
+            })
+            .ok_or(serenity::Error::Other("Channel does not exist."))?;
+
+        let mut count: Option<i64> = None;
+        crate::consts::DATABASE.count_rows("Screenshares", "", |val| {
+            if let sqlite::Value::Integer(co) = val[0] {
+                count = Some(co);
             }
+        });
 
-            message.content(format!(
-                "<@&{}>
+        let new_channel = crate::CONFIG
+            .guild
+            .create_channel(&ctx.http, |ch| {
+                ch.name(format!("screenshare-{}", count.unwrap_or_default() + 1))
+                    .category(category.0)
+                    .kind(ChannelType::Text)
+                    .permissions(
+                        // Iterator black magic
+                        std::iter::repeat((*ALLOW_PERMS, *DENY_PERMS))
+                            // Creator, Screensharers and in question
+                            .zip([
+                                PermissionOverwriteType::Member(command.user.id),
+                                PermissionOverwriteType::Member(in_question),
+                                PermissionOverwriteType::Role(crate::CONFIG.ss_support),
+                            ])
+                            .map(|((allow, deny), kind)| PermissionOverwrite { allow, deny, kind })
+                            .chain(std::iter::once(PermissionOverwrite {
+                                allow: Permissions::empty(),
+                                deny: Permissions::READ_MESSAGES,
+                                kind: PermissionOverwriteType::Role(crate::CONFIG.guild.0.into()),
+                            })),
+                    )
+            })
+            .await?;
+        Ok(new_channel)
+    };
+
+    if let Ok(channel) = result {
+        let mut message = CreateMessage::default();
+
+        let name = opts.get_str("ign").unwrap();
+        let player = Player::fetch_from_username(name.clone()).await?;
+        crate::alts::link(ctx, in_question, &player.0).await;
+        let playerstats = PlayerDataRequest(crate::CONFIG.hypixel_token.clone(), player)
+            .send()
+            .await
+            .unwrap_or_default();
+
+        let deadline = OffsetDateTime::now_utc() + FREEZE_DEADLINE;
+        let db_result = crate::consts::DATABASE.add_screenshare(
+            channel.id.0,
+            command.user.id.0,
+            in_question.0,
+            deadline,
+        );
+        crate::scheduler::schedule(JobKind::ScreenshareDeadline, channel.id.0, "", deadline);
+        super::ticket::watch_inactivity(channel.id);
+        if db_result.is_err() {
+            channel
+                .send_message(&ctx.http, |x| {
+                    x.content(
+                        "An error occured in the database. The ticket may not work as expected.",
+                    )
+                })
+                .await?;
+        }
+
+        message.content(format!(
+            "<@&{}>
 Please explain how <@{}> is cheating and screenshots of you telling them
 not to log aswell as any other info.
 ",
-                crate::consts::CONFIG.ss_support,
-                in_question
-            ));
+            crate::consts::CONFIG.ss_support,
+            in_question
+        ));
 
-            message.embed(|embed| {
-                embed
-                    .title("Screenshare Request")
-                    .description(
-                        "- Why did you request a screenshare on this member?
+        message.embed(|embed| {
+            embed
+                .title("Screenshare Request")
+                .description(
+                    "- Why did you request a screenshare on this member?
 - Please provide evidence of you telling him not to log.
 - Anything else?
 
 **NOTE**: If you do not get frozen within 15 minutes you may logout.
 ",
-                    )
-                    .field("Ign", name, false)
-                    .field(
-                        "Last login time",
-                        format!("<t:{}:R>", playerstats.last_login.unwrap_or_default()),
-                        false,
-                    )
-                    .field(
-                        "Last logout time",
-                        format!("<t:{}:R>", playerstats.last_logout.unwrap_or_default()),
-                        false,
-                    )
-            });
-            message.components(|components| {
-                components.create_action_row(|row| {
-                    row.create_button(|button| {
-                        button
-                            .label("Freeze")
-                            .style(ButtonStyle::Primary)
-                            .emoji(ReactionType::Custom {
-                                animated: false,
-                                id: crate::CONFIG.freeze_emoji,
-                                name: None,
-                            })
-                            .custom_id(format!("freeze:{}", in_question))
-                    })
-                    .create_button(|button| {
-                        button
-                            .label("Close")
-                            .style(ButtonStyle::Danger)
-                            .emoji(ReactionType::Unicode(From::from("⛔")))
-                            .custom_id(format!("close:{}", channel.id))
-                    })
-                })
-            });
-            let mut m = channel.send_message(&ctx.http, |_| &mut message).await?;
-            command
-                .create_interaction_response(&ctx.http, |resp| {
-                    resp.interaction_response_data(|data| {
-                        data.content(format!("Ticket created in {}", channel))
-                            .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
-                    })
-                })
-                .await?;
-
-            let reactions = m
-                .await_component_interaction(&ctx)
-                .timeout(Duration::from_secs(60 * 15))
-                .await;
-
-            if let Some(reactions) = reactions {
-                m.edit(&ctx, |m| {
-                    m.components(|comp| comp.set_action_rows(Default::default()))
-                })
-                .await?;
-
-                let mut chunks = reactions.data.custom_id.split(':');
-                let operation = chunks.next().unwrap_or_default();
-                let operation = Operation::try_from(operation)?;
-                let operation: Box<dyn Button> = match operation {
-                    Operation::Close => Close::new(),
-                    Operation::Freeze => Freeze::new(),
-                };
-
-                operation
-                    .click(ctx, &*reactions)
-                    .await
-                    .map_err(|x| format!("While handling button: {}", x))?;
-            } else {
-                // This is so you also can use /freeze
-                if crate::consts::DATABASE
-                    .fetch_freezes_for(in_question.0)
-                    .is_none()
-                {
-                    close::close_ticket(ctx, command.user.id, channel.id).await?;
-                }
-            }
-        } else {
-            command
-                .create_interaction_response(&ctx.http, |resp| {
-                    resp.interaction_response_data(|data| {
-                        data.content(format!(
-                            "Could not create your ticket: {}",
-                            result.as_ref().unwrap_err()
-                        ))
+                )
+                .field("Ign", name, false)
+                .field(
+                    "Last login time",
+                    format!("<t:{}:R>", playerstats.last_login.unwrap_or_default()),
+                    false,
+                )
+                .field(
+                    "Last logout time",
+                    format!("<t:{}:R>", playerstats.last_logout.unwrap_or_default()),
+                    false,
+                )
+        });
+        message.components(|components| ticket_buttons(components, in_question, true, true));
+        channel.send_message(&ctx.http, |_| &mut message).await?;
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.interaction_response_data(|data| {
+                    data.content(format!("Ticket created in {}", channel))
                         .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
-                    })
                 })
-                .await?;
-        }
-        Ok(())
-    }
-    fn new() -> Box<Self> {
-        Box::new(Self)
+            })
+            .await?;
+    } else {
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.interaction_response_data(|data| {
+                    data.content(format!(
+                        "Could not create your ticket: {}",
+                        result.as_ref().unwrap_err()
+                    ))
+                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+            })
+            .await?;
     }
+    Ok(())
 }
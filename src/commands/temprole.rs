@@ -0,0 +1,237 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use bridge_scrims::interact_opts::InteractOpts;
+use serenity::{
+    async_trait,
+    client::Context,
+    model::{
+        id::{RoleId, UserId},
+        interactions::{
+            application_command::{
+                ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+                ApplicationCommandOptionType, ApplicationCommandPermissionType,
+            },
+            InteractionApplicationCommandCallbackDataFlags,
+        },
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use super::Command;
+use crate::consts::{CONFIG, DATABASE};
+use crate::db::JobKind;
+
+pub struct TempRole;
+
+#[async_trait]
+impl Command for TempRole {
+    fn name(&self) -> String {
+        String::from("temprole")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Roles that are removed automatically.")
+                    .default_permission(false)
+                    .create_option(|add| {
+                        add.name("add")
+                            .description("Gives a user a role for a limited time.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|o| {
+                                o.name("user")
+                                    .description("The user to give the role to")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::User)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("role")
+                                    .description("The role to give")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::Role)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("duration")
+                                    .description("How long the user keeps the role")
+                                    .required(true)
+                                    .kind(ApplicationCommandOptionType::Integer)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("type")
+                                    .description("The duration type. Default: Days")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::Integer)
+                                    .add_int_choice("Minutes", 60)
+                                    .add_int_choice("Hours", 60 * 60)
+                                    .add_int_choice("Days", 60 * 60 * 24)
+                            })
+                    })
+                    .create_option(|list| {
+                        list.name("list")
+                            .description("Lists the temporary roles.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|o| {
+                                o.name("user")
+                                    .description("Only list the roles of this user")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::User)
+                            })
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |p| {
+                for role in &[
+                    CONFIG.prime_head,
+                    CONFIG.private_head,
+                    CONFIG.premium_head,
+                    CONFIG.support,
+                    CONFIG.staff,
+                ] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        let cmd = &command.data.options[0];
+        match cmd.name.as_str() {
+            "add" => add(ctx, command, cmd).await,
+            "list" => list(ctx, command, cmd).await,
+            _ => Ok(()),
+        }
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
+
+async fn add(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    opts: &ApplicationCommandInteractionDataOption,
+) -> crate::Result<()> {
+    let user = UserId(opts.get_str("user").unwrap().parse()?);
+    let role = RoleId(opts.get_str("role").unwrap().parse()?);
+    let duration =
+        opts.get_u64("duration").unwrap_or(7) * opts.get_u64("type").unwrap_or(60 * 60 * 24);
+    let until = OffsetDateTime::now_utc() + Duration::from_secs(duration);
+
+    // Same hierarchy check as for bans: staff can only hand out roles below their top role
+    let staff_roles = command
+        .member
+        .as_ref()
+        .unwrap()
+        .roles(&ctx.cache)
+        .await
+        .unwrap_or_default();
+    let allowed = match role.to_role_cached(&ctx.cache).await {
+        Some(role) => !role.managed && Some(&role) < staff_roles.iter().max(),
+        None => false,
+    };
+    if !allowed {
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.interaction_response_data(|data| {
+                    data.content(format!("You do not have permission to give <@&{}>", role))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let mut member = CONFIG.guild.member(&ctx.http, user).await?;
+    member.add_role(&ctx.http, role).await?;
+    let db_result = DATABASE.add_temp_role(user.0, role.0, until);
+    crate::scheduler::schedule(JobKind::TempRole, user.0, &role.to_string(), until);
+
+    CONFIG
+        .mod_logs
+        .unwrap_or(CONFIG.support_bans)
+        .send_message(&ctx.http, |msg| {
+            msg.embed(|e| {
+                e.title("Temporary role added")
+                    .field("User", format!("<@{}>", user), false)
+                    .field("Role", format!("<@&{}>", role), false)
+                    .field(
+                        "Expires",
+                        format!("<t:{}:R>", until.unix_timestamp()),
+                        false,
+                    )
+                    .field("Staff", format!("<@{}>", command.user.id), false)
+                    .color(Color::BLURPLE)
+            })
+        })
+        .await?;
+    command
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.interaction_response_data(|data| {
+                let mut content = format!(
+                    "Gave <@{}> <@&{}> until <t:{}>.",
+                    user,
+                    role,
+                    until.unix_timestamp()
+                );
+                if let Err(e) = db_result {
+                    content.push_str(&format!(
+                        "\nWARNING: the database responded with an error: {}",
+                        e
+                    ));
+                }
+                data.content(content).allowed_mentions(|m| m.empty_parse())
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+async fn list(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    opts: &ApplicationCommandInteractionDataOption,
+) -> crate::Result<()> {
+    let user = opts.get_str("user").and_then(|x| x.parse::<u64>().ok());
+    let mut desc = String::new();
+    for temp_role in DATABASE
+        .fetch_temp_roles()
+        .into_iter()
+        .filter(|x| user.is_none_or(|user| x.user == user))
+    {
+        writeln!(
+            desc,
+            "- <@{}>: <@&{}> until <t:{}:R>",
+            temp_role.user,
+            temp_role.role,
+            temp_role.date.unix_timestamp()
+        )?;
+    }
+    if desc.is_empty() {
+        desc = String::from("There are no temporary roles.");
+    }
+    command
+        .create_interaction_response(&ctx.http, |resp| {
+            resp.interaction_response_data(|data| {
+                data.create_embed(|e| e.title("Temporary Roles").description(desc))
+            })
+        })
+        .await?;
+    Ok(())
+}
@@ -1,23 +1,43 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
 use bridge_scrims::interact_opts::InteractOpts;
 use serenity::{
     async_trait,
     client::Context,
     model::{
-        channel::{PermissionOverwrite, PermissionOverwriteType},
-        id::UserId,
+        channel::{ChannelType, PermissionOverwrite, PermissionOverwriteType, ReactionType},
+        id::{ChannelId, UserId},
         interactions::{
             application_command::{
                 ApplicationCommandInteraction, ApplicationCommandOptionType,
                 ApplicationCommandPermissionType,
             },
-            InteractionApplicationCommandCallbackDataFlags,
+            message_component::{ButtonStyle, MessageComponentInteraction},
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
         },
+        Permissions,
     },
+    utils::Color,
 };
 
-use crate::consts::{self, CONFIG};
+use time::OffsetDateTime;
+
+use crate::consts::{self, Inactivity, TicketType, CONFIG, DATABASE};
+use crate::db::JobKind;
+use crate::scheduler;
 
-use super::Command;
+use super::screenshare::{ALLOW_PERMS, DENY_PERMS};
+use super::{Button, Command};
+
+/// How long the questionnaire waits for each answer
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+lazy_static::lazy_static! {
+    /// Users whose ticket is being opened
+    static ref OPENING: Mutex<HashSet<UserId>> = Mutex::new(HashSet::new());
+}
 
 pub struct Ticket;
 
@@ -31,20 +51,39 @@ impl Command for Ticket {
             .guild
             .create_application_command(&ctx.http, |cmd| {
                 cmd.name(self.name())
-                    .description("Adds/removes someone to an existing ticket")
+                    .description("Manages an existing ticket")
+                    .create_option(|opt| {
+                        opt.name("add")
+                            .description("Adds someone to the ticket")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|opt| {
+                                opt.name("target")
+                                    .description("The user that is added")
+                                    .kind(ApplicationCommandOptionType::User)
+                                    .required(true)
+                            })
+                    })
                     .create_option(|opt| {
-                        opt.name("operation")
-                            .description("Wether to add or remove someone")
-                            .required(true)
-                            .kind(ApplicationCommandOptionType::String)
-                            .add_string_choice("Add", "a")
-                            .add_string_choice("Remove", "r")
+                        opt.name("remove")
+                            .description("Removes someone from the ticket")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|opt| {
+                                opt.name("target")
+                                    .description("The user that is removed")
+                                    .kind(ApplicationCommandOptionType::User)
+                                    .required(true)
+                            })
                     })
                     .create_option(|opt| {
-                        opt.name("target")
-                            .description("The user that is affected by the change")
-                            .kind(ApplicationCommandOptionType::User)
-                            .required(true)
+                        opt.name("keepopen")
+                            .description("Stops the ticket from being closed for inactivity")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|opt| {
+                                opt.name("enabled")
+                                    .description("Whether to keep the ticket open. Default: true")
+                                    .kind(ApplicationCommandOptionType::Boolean)
+                                    .required(false)
+                            })
                     })
                     .default_permission(false)
             })
@@ -53,7 +92,11 @@ impl Command for Ticket {
         crate::CONFIG
             .guild
             .create_application_command_permission(&ctx.http, command.id, |perm| {
-                for role in &[CONFIG.ss_support, CONFIG.staff] {
+                let ticket_roles = CONFIG.tickets.iter().flat_map(|x| &x.staff_roles);
+                for role in [&CONFIG.ss_support, &CONFIG.staff]
+                    .into_iter()
+                    .chain(ticket_roles)
+                {
                     perm.create_permission(|perm| {
                         perm.kind(ApplicationCommandPermissionType::Role)
                             .permission(true)
@@ -71,8 +114,7 @@ impl Command for Ticket {
         ctx: &Context,
         command: &ApplicationCommandInteraction,
     ) -> crate::Result<()> {
-        let who = UserId(command.get_str("target").unwrap().parse()?);
-        let operation = command.get_str("operation").unwrap();
+        let opts = &command.data.options[0];
         let channel = command
             .channel_id
             .to_channel(&ctx.http)
@@ -83,6 +125,7 @@ impl Command for Ticket {
         if consts::DATABASE
             .fetch_screenshares_for(channel.id.0)
             .is_none()
+            && consts::DATABASE.fetch_ticket(channel.id.0).is_none()
         {
             command
                 .create_interaction_response(&ctx.http, |resp| {
@@ -96,14 +139,15 @@ impl Command for Ticket {
             return Ok(());
         }
 
-        match operation.as_str() {
-            "a" => {
+        match opts.name.as_str() {
+            "add" => {
+                let who = UserId(opts.get_str("target").unwrap().parse()?);
                 channel
                     .create_permission(
                         &ctx.http,
                         &PermissionOverwrite {
-                            allow: *super::screenshare::ALLOW_PERMS,
-                            deny: *super::screenshare::DENY_PERMS,
+                            allow: *ALLOW_PERMS,
+                            deny: *DENY_PERMS,
                             kind: PermissionOverwriteType::Member(who),
                         },
                     )
@@ -116,7 +160,8 @@ impl Command for Ticket {
                     })
                     .await?;
             }
-            "r" => {
+            "remove" => {
+                let who = UserId(opts.get_str("target").unwrap().parse()?);
                 channel
                     .delete_permission(&ctx.http, PermissionOverwriteType::Member(who))
                     .await?;
@@ -128,6 +173,29 @@ impl Command for Ticket {
                     })
                     .await?;
             }
+            "keepopen" => {
+                let keep_open = opts.get_bool("enabled").unwrap_or(true);
+                DATABASE.set_kept_open(channel.id.0, keep_open)?;
+                if keep_open {
+                    for step in ["warn", "close"] {
+                        scheduler::cancel(JobKind::TicketInactivity, channel.id.0, step);
+                    }
+                } else {
+                    watch_inactivity(channel.id);
+                }
+                let content = if keep_open {
+                    "This ticket will not be closed for inactivity."
+                } else if inactivity_rules(channel.id.0).is_none() {
+                    "No inactivity rules apply to this ticket, so it will not be closed for inactivity."
+                } else {
+                    "This ticket will be closed if it becomes inactive."
+                };
+                command
+                    .create_interaction_response(&ctx.http, |resp| {
+                        resp.interaction_response_data(|data| data.content(content))
+                    })
+                    .await?;
+            }
             _ => {
                 command
                     .create_interaction_response(&ctx.http, |resp| {
@@ -146,3 +214,354 @@ impl Command for Ticket {
         Box::new(Self)
     }
 }
+
+#[async_trait]
+impl Button for Ticket {
+    fn prefix(&self) -> String {
+        self.name()
+    }
+    async fn click(
+        &self,
+        ctx: &Context,
+        command: &MessageComponentInteraction,
+    ) -> crate::Result<()> {
+        let name = command.data.custom_id.split(':').nth(1).unwrap_or_default();
+        let kind = match CONFIG.tickets.iter().find(|x| x.name == name) {
+            Some(kind) => kind,
+            None => return Ok(()),
+        };
+        let user = &command.user;
+        // Opening takes several requests, which can take longer than Discord waits for a response
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|data| {
+                        data.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+            })
+            .await?;
+        // A double click must not open two tickets
+        if !OPENING.lock().unwrap().insert(user.id) {
+            command
+                .edit_original_interaction_response(&ctx.http, |r| {
+                    r.content("Your ticket is already being opened.")
+                })
+                .await?;
+            return Ok(());
+        }
+        let existing = DATABASE
+            .fetch_tickets(Some(user.id.0))
+            .into_iter()
+            .find(|x| x.kind == kind.name);
+        let result = match existing {
+            Some(ticket) => Err(format!("You already have a ticket in <#{}>", ticket.id)),
+            None => open(ctx, kind, user.id, &user.name)
+                .await
+                .map_err(|e| format!("Could not create your ticket: {}", e)),
+        };
+        OPENING.lock().unwrap().remove(&user.id);
+        if let Ok(channel) = result {
+            let (ctx, creator) = (ctx.clone(), user.id);
+            tokio::spawn(async move {
+                if let Err(e) = questionnaire(ctx, kind, creator, channel).await {
+                    tracing::error!("Questionnaire of ticket {} failed: {}", channel, e);
+                }
+            });
+        }
+        command
+            .edit_original_interaction_response(&ctx.http, |r| {
+                r.content(match &result {
+                    Ok(channel) => format!("Ticket created in <#{}>", channel),
+                    Err(e) => e.clone(),
+                })
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+/// Creates the ticket channel, only visible to the creator and the staff of the ticket type
+async fn open(
+    ctx: &Context,
+    kind: &TicketType,
+    creator: UserId,
+    creator_name: &str,
+) -> crate::Result<ChannelId> {
+    let mut count: Option<i64> = None;
+    DATABASE.count_rows(
+        "Tickets",
+        &format!("where kind = '{}'", kind.name.replace('\'', "''")),
+        |val| {
+            if let sqlite::Value::Integer(co) = val[0] {
+                count = Some(co);
+            }
+        },
+    );
+    let name = kind
+        .channel_name
+        .replace("{name}", &kind.name)
+        .replace("{user}", creator_name)
+        .replace("{number}", &(count.unwrap_or_default() + 1).to_string());
+    let overwrites: Vec<_> = std::iter::once(PermissionOverwriteType::Member(creator))
+        .chain(
+            kind.staff_roles
+                .iter()
+                .map(|x| PermissionOverwriteType::Role(*x)),
+        )
+        .map(|kind| PermissionOverwrite {
+            allow: *ALLOW_PERMS,
+            deny: *DENY_PERMS,
+            kind,
+        })
+        .chain(std::iter::once(PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::READ_MESSAGES,
+            kind: PermissionOverwriteType::Role(CONFIG.guild.0.into()),
+        }))
+        .collect();
+    let channel = CONFIG
+        .guild
+        .create_channel(&ctx.http, |ch| {
+            ch.name(name)
+                .category(kind.category)
+                .kind(ChannelType::Text)
+                .permissions(overwrites)
+        })
+        .await?;
+    if let Err(e) = DATABASE.add_ticket(channel.id.0, &kind.name, creator.0) {
+        channel
+            .say(
+                &ctx.http,
+                format!(
+                    "An error occured in the database. The ticket may not work as expected: {}",
+                    e
+                ),
+            )
+            .await?;
+    }
+    channel
+        .send_message(&ctx.http, |m| {
+            m.content(format!("<@{}>", creator))
+                .embed(|e| {
+                    e.title(&kind.label)
+                        .description(
+                            "Please answer the questions below. A staff member will be with you soon.",
+                        )
+                        .color(Color::BLURPLE)
+                })
+                .components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_button(|b| {
+                            b.label("Close")
+                                .style(ButtonStyle::Danger)
+                                .emoji(ReactionType::Unicode(From::from("⛔")))
+                                .custom_id("close")
+                        })
+                    })
+                })
+        })
+        .await?;
+    watch_inactivity(channel.id);
+    Ok(channel.id)
+}
+
+/// The inactivity rules of the open ticket in the channel
+pub fn inactivity_rules(channel: u64) -> Option<Inactivity> {
+    if DATABASE.fetch_screenshares_for(channel).is_some() {
+        return CONFIG.screenshare_inactivity;
+    }
+    let ticket = DATABASE.fetch_ticket(channel)?;
+    CONFIG
+        .tickets
+        .iter()
+        .find(|x| x.name == ticket.kind)?
+        .inactivity
+}
+
+/// Mentions of the people who take part in the ticket in the channel: the creator and the claimer
+/// of a screenshare, or the creator and the staff roles of a support ticket
+pub fn participants(channel: u64) -> String {
+    if let Some(screenshare) = DATABASE.fetch_screenshares_for(channel) {
+        return std::iter::once(screenshare.creator)
+            .chain(screenshare.claimer)
+            .map(|x| format!("<@{}> ", x))
+            .collect();
+    }
+    let ticket = match DATABASE.fetch_ticket(channel) {
+        Some(ticket) => ticket,
+        None => return String::new(),
+    };
+    let staff_roles = CONFIG
+        .tickets
+        .iter()
+        .find(|x| x.name == ticket.kind)
+        .map_or(&[][..], |x| &x.staff_roles[..]);
+    std::iter::once(format!("<@{}> ", ticket.creator))
+        .chain(staff_roles.iter().map(|x| format!("<@&{}> ", x)))
+        .collect()
+}
+
+/// Schedules the inactivity warning of a ticket, if its type has inactivity rules
+pub fn watch_inactivity(channel: ChannelId) {
+    if let Some(rules) = inactivity_rules(channel.0) {
+        scheduler::schedule(
+            JobKind::TicketInactivity,
+            channel.0,
+            "warn",
+            OffsetDateTime::now_utc() + Duration::from_secs(rules.warn_after),
+        );
+    }
+}
+
+/// When someone last wrote in the ticket. Messages of bots, like the inactivity warning, don't
+/// count.
+pub async fn last_activity(ctx: &Context, channel: ChannelId) -> serenity::Result<OffsetDateTime> {
+    let messages = channel.messages(&ctx.http, |r| r.limit(50)).await?;
+    let last = messages
+        .iter()
+        .find(|x| !x.author.bot)
+        .map_or_else(|| channel.created_at(), |x| x.timestamp);
+    Ok(OffsetDateTime::from_unix_timestamp(last.timestamp())
+        .unwrap_or_else(|_| OffsetDateTime::now_utc()))
+}
+
+/// Asks the questions of the ticket type one after another and pings the staff with the answers
+async fn questionnaire(
+    ctx: Context,
+    kind: &'static TicketType,
+    creator: UserId,
+    channel: ChannelId,
+) -> serenity::Result<()> {
+    let mut answers = Vec::new();
+    for question in &kind.questions {
+        channel.say(&ctx.http, question).await?;
+        let answer = channel
+            .await_reply(&ctx)
+            .author_id(creator)
+            .timeout(ANSWER_TIMEOUT)
+            .await;
+        match answer {
+            Some(answer) => answers.push((question, answer.content.clone())),
+            None => break,
+        }
+    }
+    let pings: String = kind
+        .staff_roles
+        .iter()
+        .map(|x| format!("<@&{}> ", x))
+        .collect();
+    channel
+        .send_message(&ctx.http, |m| {
+            m.content(pings).embed(|e| {
+                e.title("Answers").color(Color::BLURPLE);
+                for (question, answer) in &answers {
+                    let mut answer: String = answer.chars().take(1000).collect();
+                    if answer.is_empty() {
+                        answer = String::from("-");
+                    }
+                    e.field(question, answer, false);
+                }
+                if answers.len() < kind.questions.len() {
+                    e.description("Not every question was answered.");
+                }
+                e
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+pub struct TicketPanel;
+
+#[async_trait]
+impl Command for TicketPanel {
+    fn name(&self) -> String {
+        String::from("ticketpanel")
+    }
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx.http, |cmd| {
+                cmd.name(self.name())
+                    .description("Sends a message with a button for each ticket type")
+                    .default_permission(false)
+                    .create_option(|opt| {
+                        opt.name("channel")
+                            .description("Where the panel is sent. Default: this channel")
+                            .kind(ApplicationCommandOptionType::Channel)
+                            .required(false)
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx.http, command.id, |perm| {
+                perm.create_permission(|perm| {
+                    perm.kind(ApplicationCommandPermissionType::Role)
+                        .permission(true)
+                        .id(CONFIG.staff.0)
+                })
+            })
+            .await?;
+        Ok(())
+    }
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        let channel = match command.get_str("channel") {
+            Some(channel) => ChannelId(channel.parse()?),
+            None => command.channel_id,
+        };
+        let result = if CONFIG.tickets.is_empty() {
+            Err(String::from("There are no ticket types in the config."))
+        } else {
+            channel
+                .send_message(&ctx.http, |m| {
+                    m.embed(|e| {
+                        e.title("Support")
+                            .color(Color::BLURPLE)
+                            .description("Press a button below to open a ticket.");
+                        for kind in &CONFIG.tickets {
+                            if let Some(description) = &kind.description {
+                                e.field(&kind.label, description, false);
+                            }
+                        }
+                        e
+                    })
+                    .components(|c| {
+                        // Discord allows 5 buttons per row
+                        for kinds in CONFIG.tickets.chunks(5) {
+                            c.create_action_row(|row| {
+                                for kind in kinds {
+                                    row.create_button(|b| {
+                                        b.label(&kind.label)
+                                            .style(ButtonStyle::Primary)
+                                            .custom_id(format!("ticket:{}", kind.name))
+                                    });
+                                }
+                                row
+                            });
+                        }
+                        c
+                    })
+                })
+                .await
+                .map(|_| format!("Sent the ticket panel to <#{}>", channel))
+                .map_err(|e| format!("Could not send the ticket panel: {}", e))
+        };
+        command
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.interaction_response_data(|data| {
+                    data.content(result.unwrap_or_else(|e| e))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+            })
+            .await?;
+        Ok(())
+    }
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
@@ -6,6 +6,7 @@ use chrono::{
 };
 use serenity::{
     async_trait,
+    builder::CreateEmbed,
     model::{
         id::UserId,
         interactions::{
@@ -19,8 +20,12 @@ use serenity::{
     prelude::Context,
     utils::Color,
 };
+use time::OffsetDateTime;
 
-use crate::consts::CONFIG;
+use crate::{
+    consts::CONFIG,
+    db::{JobKind, PunishmentKind},
+};
 
 pub struct Timeout {}
 
@@ -57,6 +62,12 @@ impl Command for Timeout {
                             .add_int_choice("Hours", 60 * 60)
                             .add_int_choice("Days", 60 * 60 * 24)
                     })
+                    .create_option(|o| {
+                        o.name("reason")
+                            .description("The reason for the timeout.")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::String)
+                    })
                     .default_permission(false)
             })
             .await?;
@@ -90,6 +101,9 @@ impl Command for Timeout {
             .to_user(&ctx.http)
             .await?;
 
+        let reason = command
+            .get_str("reason")
+            .unwrap_or_else(|| String::from("No reason given."));
         let duration = command.get_u64("duration").unwrap_or(3);
         let mult = command.get_i64("type").unwrap_or(60 * 60);
 
@@ -123,20 +137,61 @@ impl Command for Timeout {
         let resp = member
             .disable_communication_until_datetime(&ctx.http, end)
             .await;
+        if let Err(err) = resp {
+            command
+                .edit_original_interaction_response(&ctx, |r| {
+                    r.content(format!("Could not timeout {}: {}", user.tag(), err))
+                })
+                .await?;
+            return Ok(());
+        }
+
+        let until = OffsetDateTime::from_unix_timestamp(end.timestamp())?;
+        let db_result = crate::consts::DATABASE.add_timeout(user.id.0, until);
+        crate::scheduler::schedule(JobKind::Untimeout, user.id.0, "", until);
+        let case = super::case::record(
+            PunishmentKind::Timeout,
+            user.id.0,
+            Some(command.user.id.0),
+            &reason,
+            Some(until),
+        );
+        tokio::spawn(crate::escalation::check(
+            ctx.clone(),
+            user.id,
+            PunishmentKind::Timeout,
+        ));
 
+        let mut embed = CreateEmbed::default();
+        embed
+            .title("User Timed out!")
+            .description(format!(
+                "The user {} has been timed out until <t:{}>.",
+                user.tag(),
+                end.timestamp()
+            ))
+            .field("Reason", format!("`{}`", reason), false)
+            .field("Staff", format!("<@{}>", command.user.id), false)
+            .color(Color::new(0x1abc9c));
+        if let Some(case) = case {
+            embed.field("Case", format!("#{}", case), false);
+        }
+        CONFIG
+            .mod_logs
+            .unwrap_or(CONFIG.support_bans)
+            .send_message(&ctx.http, |msg| msg.set_embed(embed.clone()))
+            .await?;
+
+        if let Err(e) = db_result {
+            embed.footer(|f| {
+                f.text(format!(
+                    "WARNING: the database responded with an error: {}",
+                    e
+                ))
+            });
+        }
         command
-            .edit_original_interaction_response(&ctx, |r| match resp {
-                Ok(()) => r.create_embed(|e| {
-                    e.title("User Timed out!")
-                        .description(format!(
-                            "The user {} has been timed out until <t:{}>.",
-                            user.tag(),
-                            end.timestamp()
-                        ))
-                        .color(Color::new(0x1abc9c))
-                }),
-                Err(err) => r.content(format!("Could not timeout {}: {}", user.tag(), err)),
-            })
+            .edit_original_interaction_response(&ctx, |r| r.add_embed(embed))
             .await?;
 
         Ok(())
@@ -148,3 +203,114 @@ impl Command for Timeout {
         Box::new(Timeout {})
     }
 }
+
+pub struct Untimeout;
+
+#[async_trait]
+impl Command for Untimeout {
+    fn name(&self) -> String {
+        "untimeout".to_string()
+    }
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let cmd = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Ends the timeout of a user.")
+                    .create_option(|o| {
+                        o.name("user")
+                            .description("The person whose timeout you would like to end.")
+                            .required(true)
+                            .kind(ApplicationCommandOptionType::User)
+                    })
+                    .create_option(|o| {
+                        o.name("reason")
+                            .description("The reason for ending the timeout.")
+                            .required(false)
+                            .kind(ApplicationCommandOptionType::String)
+                    })
+                    .default_permission(false)
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, cmd.id, |p| {
+                for role in &[CONFIG.support, CONFIG.trial_support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+
+        let user = UserId(command.get_str("user").unwrap().parse()?)
+            .to_user(&ctx.http)
+            .await?;
+        let reason = command
+            .get_str("reason")
+            .unwrap_or_else(|| String::from("No reason given."));
+
+        let mut member = ctx.http.get_member(CONFIG.guild.0, user.id.0).await?;
+        if let Err(err) = member.enable_communication(&ctx.http).await {
+            command
+                .edit_original_interaction_response(&ctx, |r| {
+                    r.content(format!(
+                        "Could not end the timeout of {}: {}",
+                        user.tag(),
+                        err
+                    ))
+                })
+                .await?;
+            return Ok(());
+        }
+        // The timeout may not have been given through the bot
+        let _ = crate::consts::DATABASE.remove_entry("Timeouts", user.id.0);
+        crate::scheduler::cancel(JobKind::Untimeout, user.id.0, "");
+        let case = super::case::record(
+            PunishmentKind::Untimeout,
+            user.id.0,
+            Some(command.user.id.0),
+            &reason,
+            None,
+        );
+
+        let mut embed = CreateEmbed::default();
+        embed
+            .title("Timeout ended!")
+            .description(format!("The timeout of {} has been ended.", user.tag()))
+            .field("Reason", format!("`{}`", reason), false)
+            .field("Staff", format!("<@{}>", command.user.id), false)
+            .color(Color::new(0x1abc9c));
+        if let Some(case) = case {
+            embed.field("Case", format!("#{}", case), false);
+        }
+        CONFIG
+            .mod_logs
+            .unwrap_or(CONFIG.support_bans)
+            .send_message(&ctx.http, |msg| msg.set_embed(embed.clone()))
+            .await?;
+        command
+            .edit_original_interaction_response(&ctx, |r| r.add_embed(embed))
+            .await?;
+
+        Ok(())
+    }
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use bridge_scrims::interact_opts::InteractOpts;
+use chrono::NaiveDate;
+use serenity::{
+    async_trait,
+    client::Context,
+    http::AttachmentType,
+    model::interactions::{
+        application_command::{
+            ApplicationCommandInteraction, ApplicationCommandOptionType,
+            ApplicationCommandPermissionType,
+        },
+        InteractionResponseType,
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use super::Command;
+use crate::consts::{CONFIG, DATABASE, DATABASE_PATH};
+use crate::db::ArchivedTranscript;
+
+/// How many results are listed
+const MAX_RESULTS: usize = 10;
+/// How many transcripts of the results are uploaded
+const MAX_UPLOADS: usize = 5;
+
+fn archive_path(channel: u64) -> PathBuf {
+    DATABASE_PATH
+        .join("transcripts")
+        .join(format!("{}.html", channel))
+}
+
+/// Stores the HTML transcript in the archive directory and its plain text in the database
+pub fn archive(transcript: &ArchivedTranscript, html: &str, text: &str) -> crate::Result<()> {
+    let path = archive_path(transcript.channel);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, html)?;
+    DATABASE.add_transcript(transcript, text)?;
+    Ok(())
+}
+
+/// Parses a `YYYY-MM-DD` date, `days` later
+fn parse_date(date: &str, days: i64) -> Result<OffsetDateTime, String> {
+    let error = || format!("`{}` is not a date like 2022-01-31", date);
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| error())?;
+    let time = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() + days * 24 * 60 * 60;
+    OffsetDateTime::from_unix_timestamp(time).map_err(|_| error())
+}
+
+pub struct Transcripts;
+
+#[async_trait]
+impl Command for Transcripts {
+    fn name(&self) -> String {
+        String::from("transcripts")
+    }
+
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let command = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Archived ticket transcripts.")
+                    .default_permission(false)
+                    .create_option(|search| {
+                        search
+                            .name("search")
+                            .description("Finds closed tickets and uploads their transcripts.")
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .create_sub_option(|o| {
+                                o.name("user")
+                                    .description("Someone that took part in the ticket")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::User)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("keyword")
+                                    .description("Text in the ticket name or messages")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::String)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("from")
+                                    .description("Closed on or after this date, e.g. 2022-01-31")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::String)
+                            })
+                            .create_sub_option(|o| {
+                                o.name("to")
+                                    .description("Closed on or before this date, e.g. 2022-02-28")
+                                    .required(false)
+                                    .kind(ApplicationCommandOptionType::String)
+                            })
+                    })
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, command.id, |p| {
+                for role in &[CONFIG.ss_support, CONFIG.support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        let opts = &command.data.options[0];
+        command
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+        let user = opts.get_str("user").and_then(|x| x.parse::<u64>().ok());
+        let keyword = opts.get_str("keyword");
+        let range = (
+            opts.get_str("from").map(|x| parse_date(&x, 0)).transpose(),
+            // The end date is included
+            opts.get_str("to").map(|x| parse_date(&x, 1)).transpose(),
+        );
+        let (from, to) = match range {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(e), _) | (_, Err(e)) => {
+                command
+                    .edit_original_interaction_response(&ctx.http, |r| r.content(e))
+                    .await?;
+                return Ok(());
+            }
+        };
+        if user.is_none() && keyword.is_none() && from.is_none() && to.is_none() {
+            command
+                .edit_original_interaction_response(&ctx.http, |r| {
+                    r.content("Search by at least a user, keyword or date.")
+                })
+                .await?;
+            return Ok(());
+        }
+
+        let results = DATABASE.fetch_transcripts(user, keyword.as_deref(), from, to);
+        let mut desc = String::new();
+        for transcript in results.iter().take(MAX_RESULTS) {
+            writeln!(
+                desc,
+                "- `{}` closed <t:{}:d>{}",
+                transcript.title,
+                transcript.closed_at.unix_timestamp(),
+                transcript
+                    .outcome
+                    .map(|x| format!(" as {}", x))
+                    .unwrap_or_default()
+            )?;
+        }
+        if results.len() > MAX_RESULTS {
+            writeln!(desc, "... and {} more", results.len() - MAX_RESULTS)?;
+        }
+        if desc.is_empty() {
+            desc = String::from("No transcripts found.");
+        }
+        command
+            .edit_original_interaction_response(&ctx.http, |r| {
+                r.create_embed(|e| {
+                    e.title(format!("Transcripts ({})", results.len()))
+                        .description(desc)
+                        .color(Color::BLURPLE)
+                })
+            })
+            .await?;
+
+        let files: Vec<_> = results
+            .iter()
+            .take(MAX_UPLOADS)
+            .filter_map(|transcript| {
+                let html = std::fs::read(archive_path(transcript.channel)).ok()?;
+                Some(AttachmentType::Bytes {
+                    data: html.into(),
+                    filename: format!("{}.html", transcript.title),
+                })
+            })
+            .collect();
+        if !files.is_empty() {
+            command
+                .create_followup_message(&ctx.http, |m| m.add_files(files))
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn new() -> Box<Self> {
+        Box::new(Self)
+    }
+}
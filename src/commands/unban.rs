@@ -17,7 +17,7 @@ use serenity::{
     },
 };
 
-use crate::consts::CONFIG;
+use crate::{consts::CONFIG, db::PunishmentKind};
 
 use super::Command;
 
@@ -108,8 +108,8 @@ impl UnbanType {
         let mut embed = CreateEmbed::default();
         embed.title(format!("{} was unbanned", to_unban.tag()));
         embed.field("User", format!("<@{}>", to_unban.id), false);
-        if staff_id.is_some() {
-            embed.field("Staff", format!("<@{}>", staff_id.unwrap()), false);
+        if let Some(staff_id) = staff_id {
+            embed.field("Staff", format!("<@{}>", staff_id), false);
         }
 
         embed.field("Reason", format!("`{}`", reason), false);
@@ -147,6 +147,15 @@ impl UnbanType {
             }
         }
         if result.is_ok() {
+            let kind = match self {
+                Self::Server => PunishmentKind::Unban,
+                Self::Scrim => PunishmentKind::ScrimUnban,
+            };
+            if let Some(case) =
+                super::case::record(kind, to_unban.id.0, staff_id.map(|x| x.0), &reason, None)
+            {
+                embed.field("Case", format!("#{}", case), false);
+            }
             CONFIG
                 .support_bans
                 .send_message(&http, |msg| msg.set_embed(embed.clone()))
@@ -60,7 +60,7 @@ impl Command for Unfreeze {
             .emoji(&ctx.http, crate::CONFIG.unfreeze_emoji)
             .await?;
         let user = UserId(command.get_str("player").unwrap().parse()?);
-        let unfreeze = unfreeze_user(&ctx.http, user).await?;
+        let unfreeze = unfreeze_user(&ctx.http, user, Some(command.user.id), "Unfrozen").await?;
         command
             .create_interaction_response(&ctx.http, |resp| {
                 resp.interaction_response_data(|data| {
@@ -95,7 +95,12 @@ impl Command for Unfreeze {
     }
 }
 
-pub async fn unfreeze_user(http: &Http, user: UserId) -> crate::Result<bool> {
+pub async fn unfreeze_user(
+    http: &Http,
+    user: UserId,
+    staff: Option<UserId>,
+    reason: &str,
+) -> crate::Result<bool> {
     let freeze = crate::consts::DATABASE.fetch_freezes_for(user.0);
     if freeze.is_none() {
         return Ok(false);
@@ -106,5 +111,12 @@ pub async fn unfreeze_user(http: &Http, user: UserId) -> crate::Result<bool> {
     member.remove_role(&http, crate::CONFIG.frozen).await?;
     member.add_roles(&http, &freeze.roles).await?;
     crate::consts::DATABASE.remove_entry("Freezes", user.0)?;
+    super::case::record(
+        crate::db::PunishmentKind::Unfreeze,
+        user.0,
+        staff.map(|x| x.0),
+        reason,
+        None,
+    );
     Ok(true)
 }
@@ -0,0 +1,353 @@
+use crate::commands::Command;
+use bridge_scrims::interact_opts::InteractOpts;
+use serenity::{
+    async_trait,
+    builder::CreateEmbed,
+    model::{
+        id::UserId,
+        interactions::{
+            application_command::{
+                ApplicationCommandInteraction, ApplicationCommandOptionType,
+                ApplicationCommandPermissionType,
+            },
+            InteractionResponseType,
+        },
+    },
+    prelude::Context,
+    utils::Color,
+};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use crate::{consts::CONFIG, db::PunishmentKind};
+
+pub struct Warn;
+
+#[async_trait]
+impl Command for Warn {
+    fn name(&self) -> String {
+        "warn".to_string()
+    }
+    async fn register(&self, ctx: &Context) -> crate::Result<()> {
+        let cmd = CONFIG
+            .guild
+            .create_application_command(&ctx, |c| {
+                c.name(self.name())
+                    .description("Manage the warnings of a user.")
+                    .create_option(|add| {
+                        add.kind(ApplicationCommandOptionType::SubCommand)
+                            .name("add")
+                            .description("Warn a given user.")
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::User)
+                                    .name("user")
+                                    .description("The user to warn.")
+                                    .required(true)
+                            })
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::String)
+                                    .name("reason")
+                                    .description("The reason for the warning.")
+                                    .required(true)
+                            })
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::Integer)
+                                    .name("duration")
+                                    .description("How long the warning counts. Default: forever")
+                                    .required(false)
+                            })
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::Integer)
+                                    .name("type")
+                                    .description("The duration type. Default: Days")
+                                    .required(false)
+                                    .add_int_choice("Seconds", 1)
+                                    .add_int_choice("Minutes", 60)
+                                    .add_int_choice("Hours", 60 * 60)
+                                    .add_int_choice("Days", 60 * 60 * 24)
+                            })
+                    })
+                    .create_option(|list| {
+                        list.kind(ApplicationCommandOptionType::SubCommand)
+                            .name("list")
+                            .description("The warnings for a given user.")
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::User)
+                                    .name("user")
+                                    .description("The user who's warnings to retrieve.")
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|del| {
+                        del.kind(ApplicationCommandOptionType::SubCommand)
+                            .name("remove")
+                            .description("Remove a warning from a user.")
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::User)
+                                    .name("user")
+                                    .description("The user to remove a warning from.")
+                                    .required(true)
+                            })
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::Integer)
+                                    .name("warnid")
+                                    .description("The warning id to remove.")
+                                    .required(true)
+                            })
+                    })
+                    .create_option(|clear| {
+                        clear
+                            .kind(ApplicationCommandOptionType::SubCommand)
+                            .name("clear")
+                            .description("Remove all warnings from a user.")
+                            .create_sub_option(|opt| {
+                                opt.kind(ApplicationCommandOptionType::User)
+                                    .name("user")
+                                    .description("The user to clear the warnings of.")
+                                    .required(true)
+                            })
+                    })
+                    .default_permission(false)
+            })
+            .await?;
+        CONFIG
+            .guild
+            .create_application_command_permission(&ctx, cmd.id, |p| {
+                for role in &[CONFIG.support, CONFIG.trial_support, CONFIG.staff] {
+                    p.create_permission(|perm| {
+                        perm.kind(ApplicationCommandPermissionType::Role)
+                            .id(role.0)
+                            .permission(true)
+                    });
+                }
+                p
+            })
+            .await?;
+        Ok(())
+    }
+    async fn run(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+    ) -> crate::Result<()> {
+        command
+            .create_interaction_response(&ctx, |r| {
+                r.interaction_response_data(|d| d)
+                    .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+        let cmd = &command.data.options[0];
+        let user = UserId(cmd.get_str("user").unwrap().parse()?)
+            .to_user(&ctx.http)
+            .await?;
+        let user_id = user.id;
+        match cmd.name.as_str() {
+            "add" => {
+                let now = OffsetDateTime::now_utc();
+                let reason = cmd.get_str("reason").unwrap();
+                let expires_at = cmd.get_u64("duration").map(|duration| {
+                    let factor = cmd.get_u64("type").unwrap_or(60 * 60 * 24);
+                    now + Duration::from_secs(duration * factor)
+                });
+
+                let warnid = crate::consts::DATABASE.add_warning(
+                    user_id.0,
+                    now,
+                    &reason,
+                    command.user.id.0,
+                    expires_at,
+                )?;
+                let active = crate::consts::DATABASE
+                    .fetch_warnings_for(user_id.0)
+                    .iter()
+                    .filter(|x| x.is_active())
+                    .count();
+
+                let mut embed = CreateEmbed::default();
+                embed.title(format!("{} received a warning", user.tag()));
+                embed.field("User", format!("<@{}>", user_id), false);
+                embed.field("Reason", format!("`{}`", reason), false);
+                if let Some(expires_at) = expires_at {
+                    embed.field(
+                        "Expires",
+                        format!("<t:{}:R>", expires_at.unix_timestamp()),
+                        false,
+                    );
+                } else {
+                    embed.field("Expires", "never", false);
+                }
+                embed.field("Active warnings", active, false);
+                embed.field("Staff", format!("<@{}>", command.user.id), false);
+                if let Some(case) = super::case::record(
+                    PunishmentKind::Warning,
+                    user_id.0,
+                    Some(command.user.id.0),
+                    &reason,
+                    expires_at,
+                ) {
+                    embed.field("Case", format!("#{}", case), false);
+                }
+                embed.color(Color::ORANGE);
+
+                let dm_result = user.dm(&ctx.http, |msg| msg.set_embed(embed.clone())).await;
+                if let Err(e) = dm_result {
+                    tracing::error!(
+                        "Could not DM user {} about their warning: {}",
+                        user.tag(),
+                        e
+                    );
+                }
+                CONFIG
+                    .support_bans
+                    .send_message(&ctx.http, |msg| msg.set_embed(embed.clone()))
+                    .await?;
+
+                tokio::spawn(crate::escalation::check(
+                    ctx.clone(),
+                    user_id,
+                    PunishmentKind::Warning,
+                ));
+
+                embed.footer(|f| f.text(format!("Warning id: {}", warnid)));
+                command
+                    .edit_original_interaction_response(&ctx, |r| r.add_embed(embed))
+                    .await?;
+            }
+            "list" => {
+                let warnings = crate::consts::DATABASE.fetch_warnings_for(user_id.0);
+
+                if warnings.is_empty() {
+                    command
+                        .edit_original_interaction_response(&ctx, |r| {
+                            r.create_embed(|e| {
+                                e.title("No warnings found!")
+                                    .description(format!(
+                                        "<@{}> currently has no warnings.",
+                                        user_id.0
+                                    ))
+                                    .color(Color::BLURPLE)
+                            })
+                        })
+                        .await?;
+                    return Ok(());
+                }
+                let active = warnings.iter().filter(|x| x.is_active()).count();
+                let pages = ((warnings.len() - 1) / 10) + 1;
+                for (i, chunk) in warnings.chunks(10).enumerate() {
+                    let mut embed = CreateEmbed::default();
+                    embed
+                        .title(format!("Page {} of {}", i + 1, pages))
+                        .color(Color::BLURPLE);
+                    if i == 0 {
+                        embed.description(format!(
+                            "<@{}> has {} warnings, {} of which are active:",
+                            user_id.0,
+                            warnings.len(),
+                            active
+                        ));
+                    }
+                    for warning in chunk {
+                        let expiry = match warning.expires_at {
+                            Some(expiry) => format!(", expires <t:{}:R>", expiry.unix_timestamp()),
+                            None => String::new(),
+                        };
+                        embed.field(
+                            format!(
+                                "Warning {}{}:",
+                                warning.id,
+                                if warning.is_active() {
+                                    ""
+                                } else {
+                                    " (expired)"
+                                }
+                            ),
+                            format!(
+                                "<t:{}>: `{}` by <@!{}>{}",
+                                warning.created_at.unix_timestamp(),
+                                warning.reason,
+                                warning.creator,
+                                expiry
+                            ),
+                            false,
+                        );
+                    }
+                    if i == 0 {
+                        command
+                            .edit_original_interaction_response(&ctx, |r| r.add_embed(embed))
+                            .await?;
+                    } else {
+                        command
+                            .create_followup_message(&ctx, |r| r.add_embed(embed))
+                            .await?;
+                    }
+                }
+            }
+            "remove" => {
+                let warnid = cmd.get_u64("warnid").unwrap();
+
+                if !crate::consts::DATABASE
+                    .fetch_warnings_for(user_id.0)
+                    .iter()
+                    .any(|x| x.id == warnid)
+                {
+                    command
+                        .edit_original_interaction_response(&ctx, |r| {
+                            r.create_embed(|e| {
+                                e.title("Warning not found")
+                                    .description(format!(
+                                        "<@{}> has no warning with id {}.",
+                                        user_id.0, warnid
+                                    ))
+                                    .color(Color::DARK_RED)
+                            })
+                        })
+                        .await?;
+                    return Ok(());
+                }
+
+                crate::consts::DATABASE.remove_warning(user_id.0, warnid)?;
+
+                command
+                    .edit_original_interaction_response(&ctx, |r| {
+                        r.create_embed(|e| {
+                            e.title("Warning Removed")
+                                .description(format!(
+                                    "The warning has been removed from <@{}> with id {}.",
+                                    user_id.0, warnid
+                                ))
+                                .color(Color::BLURPLE)
+                        })
+                    })
+                    .await?;
+            }
+            "clear" => {
+                let count = crate::consts::DATABASE.fetch_warnings_for(user_id.0).len();
+                crate::consts::DATABASE.clear_warnings(user_id.0)?;
+
+                command
+                    .edit_original_interaction_response(&ctx, |r| {
+                        r.create_embed(|e| {
+                            e.title("Warnings Cleared")
+                                .description(format!(
+                                    "Removed {} warnings from <@{}>.",
+                                    count, user_id.0
+                                ))
+                                .color(Color::BLURPLE)
+                        })
+                    })
+                    .await?;
+            }
+            _ => {
+                // Do nothing since this wont happen.
+            }
+        }
+
+        Ok(())
+    }
+    fn new() -> Box<Self>
+    where
+        Self: Sized,
+    {
+        Box::new(Warn)
+    }
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crate::db::Database;
+use crate::db::{Database, PunishmentKind};
 use bridge_scrims::hypixel::UUID;
 use serde::Deserialize;
 use serenity::model::id::ChannelId;
@@ -35,6 +35,177 @@ pub struct Ping {
     pub allowed_channels: Option<Vec<ChannelId>>,
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EscalationAction {
+    Ban,
+    ScrimBan,
+}
+
+#[derive(Deserialize)]
+pub struct EscalationRule {
+    /// Shown in the logs when the rule fires
+    pub name: String,
+    /// The kind of punishment that is counted
+    pub count: PunishmentKind,
+    /// The rule fires when the count reaches this number
+    pub threshold: usize,
+    /// Only count punishments from the last `within` seconds
+    pub within: Option<u64>,
+    pub action: EscalationAction,
+    /// Duration of the ban in seconds, defaults to the duration of the ban command
+    pub duration: Option<u64>,
+    /// Ask staff to confirm the action instead of applying it directly
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Deserialize, Default)]
+pub struct Escalation {
+    #[serde(default)]
+    pub rules: Vec<EscalationRule>,
+}
+
+fn default_channel_name() -> String {
+    String::from("{name}-{number}")
+}
+
+/// When quiet tickets are warned and closed, in seconds
+#[derive(Deserialize, Clone, Copy)]
+pub struct Inactivity {
+    /// The participants are warned after this long without a message
+    pub warn_after: u64,
+    /// The ticket is closed this long after the warning unless someone writes
+    pub close_after: u64,
+}
+
+#[derive(Deserialize)]
+pub struct TicketType {
+    /// Tells the ticket types apart, so it has to be unique
+    pub name: String,
+    /// Shown on the button of the ticket panel
+    pub label: String,
+    /// Shown on the ticket panel
+    pub description: Option<String>,
+    /// The category the tickets are created in
+    pub category: ChannelId,
+    /// Roles that can see and handle the tickets
+    #[serde(default)]
+    pub staff_roles: Vec<RoleId>,
+    /// Asked one after another when a ticket is opened
+    #[serde(default)]
+    pub questions: Vec<String>,
+    /// `{name}` is replaced by the ticket type, `{user}` by the name of the creator and
+    /// `{number}` by the ticket number
+    #[serde(default = "default_channel_name")]
+    pub channel_name: String,
+    /// Where closed tickets are logged, defaults to `mod_logs`
+    pub logs: Option<ChannelId>,
+    pub inactivity: Option<Inactivity>,
+}
+
+/// Scrimban durations in seconds for screenshare outcomes, defaulting to the duration of the
+/// scrimban command
+#[derive(Deserialize, Default)]
+pub struct ScreenshareBans {
+    pub admitted: Option<u64>,
+    pub refused: Option<u64>,
+    pub cheating: Option<u64>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutomodAction {
+    Delete,
+    Warn,
+    Timeout,
+    ScrimBan,
+}
+
+#[derive(Deserialize)]
+pub struct FloodRule {
+    /// The rule fires when a user sends this many messages within `seconds`
+    pub messages: usize,
+    pub seconds: u64,
+    pub action: AutomodAction,
+    /// Duration of the punishment in seconds
+    pub duration: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateRule {
+    /// The rule fires when a user sends the same message this many times within `seconds`
+    pub count: usize,
+    pub seconds: u64,
+    pub action: AutomodAction,
+    pub duration: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct MentionRule {
+    /// The most user and role mentions a single message may contain
+    pub max: usize,
+    pub action: AutomodAction,
+    pub duration: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct InviteRule {
+    /// Channels or categories where invites may be posted
+    #[serde(default)]
+    pub allowed_channels: Vec<ChannelId>,
+    pub action: AutomodAction,
+    pub duration: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct BlockedRule {
+    /// Matched as whole words, ignoring case
+    #[serde(default)]
+    pub words: Vec<String>,
+    #[serde(default)]
+    pub regexes: Vec<String>,
+    pub action: AutomodAction,
+    pub duration: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct Automod {
+    /// Where every automod hit is logged
+    pub log: Option<ChannelId>,
+    /// Roles that are never moderated, on top of the support and staff roles
+    #[serde(default)]
+    pub exempt_roles: Vec<RoleId>,
+    pub flood: Option<FloodRule>,
+    pub duplicate: Option<DuplicateRule>,
+    pub mentions: Option<MentionRule>,
+    pub invites: Option<InviteRule>,
+    pub blocked: Option<BlockedRule>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RaidAction {
+    /// Raise the verification level of the guild for the duration of the raid
+    Verification,
+    /// Time out everyone who joins during the raid
+    Timeout,
+}
+
+#[derive(Deserialize)]
+pub struct Raid {
+    /// Raid mode starts when this many users join within `seconds`
+    pub joins: usize,
+    pub seconds: u64,
+    /// Only count accounts younger than this many seconds
+    pub account_age: Option<u64>,
+    pub action: RaidAction,
+    /// How long new joiners are timed out for, in seconds
+    pub timeout: Option<u64>,
+    /// Where raid alerts are posted, defaults to `support_bans`
+    pub alerts: Option<ChannelId>,
+}
+
 #[derive(Deserialize)]
 pub struct Config {
     pub bot_token: String,
@@ -44,6 +215,9 @@ pub struct Config {
     pub guild: GuildId,
 
     pub queue_categories: Vec<ChannelId>,
+    /// The channels that `/lockdown` and `/slowmode` change when all channels are selected
+    #[serde(default)]
+    pub lockdown_channels: Vec<ChannelId>,
 
     pub prime_council: RoleId,
     pub prime_head: RoleId,
@@ -58,6 +232,8 @@ pub struct Config {
     pub support: RoleId,
     pub trial_support: RoleId,
     pub support_bans: ChannelId,
+    /// Where timeouts are logged, defaults to `support_bans`
+    pub mod_logs: Option<ChannelId>,
     pub screenshare_requests: ChannelId,
     pub frozen: RoleId,
     pub frozen_chat: ChannelId,
@@ -65,16 +241,34 @@ pub struct Config {
     pub ss_logs: ChannelId,
     pub freeze_emoji: EmojiId,
     pub unfreeze_emoji: EmojiId,
+    #[serde(default)]
+    pub screenshare_bans: ScreenshareBans,
+    pub screenshare_inactivity: Option<Inactivity>,
+    #[serde(default)]
+    pub tickets: Vec<TicketType>,
 
     pub polls: ChannelId,
     pub clips: ChannelId,
     pub reaction_logs: ChannelId,
+    /// Where ban appeals are reviewed, defaults to `support_bans`
+    pub appeals: Option<ChannelId>,
+    /// The bot can only read the DMs of users it shares a server with, so server bans can only be
+    /// appealed by members of this server
+    pub appeal_guild: Option<GuildId>,
+    /// Invite to `appeal_guild`, sent to server banned users
+    pub appeal_invite: Option<String>,
 
     pub prefabs: HashMap<String, String>,
 
     pub member_count: MemberCount,
 
     pub pings: Vec<Ping>,
+
+    #[serde(default)]
+    pub escalation: Escalation,
+    #[serde(default)]
+    pub automod: Automod,
+    pub raid: Option<Raid>,
 }
 
 lazy_static::lazy_static! {
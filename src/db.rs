@@ -1,6 +1,7 @@
 pub use crate::model::*;
 use std::{
     cmp::Reverse,
+    collections::HashMap,
     str::FromStr,
     sync::{Mutex, MutexGuard},
 };
@@ -44,6 +45,14 @@ impl Database {
         )
         .expect("Could not initialize database");
 
+        conn.execute(
+            "create table if not exists Timeouts (
+                id integer,
+                time integer
+            )",
+        )
+        .expect("Could not initialize database");
+
         conn.execute(
             "create table if not exists Notes (
                 userid integer,
@@ -72,6 +81,18 @@ impl Database {
             )",
         )
         .expect("Could not initialize database");
+        // Closed tickets are kept for the moderation history. These columns were added later, so
+        // the errors for already existing columns are ignored.
+        for column in [
+            "created_at integer",
+            "closed_at integer",
+            "closer integer",
+            "deadline integer",
+            "claimer integer",
+            "outcome text",
+        ] {
+            let _ = conn.execute(format!("alter table Screenshares add column {}", column));
+        }
 
         conn.execute(
             "create table if not exists Freezes (
@@ -90,6 +111,154 @@ impl Database {
         )
         .expect("Could not initialize database");
 
+        conn.execute(
+            "create table if not exists Warnings (
+                userid integer,
+                id integer,
+                created_at integer,
+                reason text,
+                creator integer,
+                expires_at integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists Punishments (
+                id integer primary key autoincrement,
+                kind text,
+                user integer,
+                staff integer,
+                reason text,
+                created_at integer,
+                expires_at integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists RaidMembers (
+                id integer,
+                joined_at integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists RaidMode (
+                id integer primary key,
+                previous_level integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists AccountLinks (
+                userid integer,
+                uuid text,
+                created_at integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists Appeals (
+                id integer primary key autoincrement,
+                user integer,
+                kind text,
+                case_id integer,
+                text text,
+                created_at integer,
+                status text,
+                reviewer integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists ScheduledReverts (
+                channel integer,
+                change text,
+                previous text,
+                time integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists Purges (
+                staff integer,
+                channel integer,
+                count integer,
+                created_at integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists TempRoles (
+                userid integer,
+                role integer,
+                time integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists ScheduledJobs (
+                id integer primary key autoincrement,
+                kind text,
+                target integer,
+                data text,
+                time integer,
+                attempts integer
+            )",
+        )
+        .expect("Could not initialize database");
+        // Added later, the error for an already existing column is ignored
+        let _ = conn.execute("alter table ScheduledJobs add column attempts integer");
+
+        conn.execute(
+            "create table if not exists Tickets (
+                id integer primary key,
+                kind text,
+                creator integer,
+                created_at integer,
+                closed_at integer,
+                closer integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists PendingEscalations (
+                id integer primary key autoincrement,
+                user integer,
+                rule text,
+                created_at integer
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists KeptOpen (
+                id integer primary key
+            )",
+        )
+        .expect("Could not initialize database");
+
+        conn.execute(
+            "create table if not exists Transcripts (
+                channel integer primary key,
+                title text,
+                participants text,
+                outcome text,
+                closed_at integer,
+                content text
+            )",
+        )
+        .expect("Could not initialize database");
+
         Self {
             sqlite: Mutex::new(conn),
         }
@@ -148,7 +317,7 @@ impl Database {
         tracing::info!("Fetching bans");
         let mut result = Vec::new();
         self.fetch_rows("ScheduledUnbans", "", |row| {
-            let id = row.get(0).unwrap().as_integer().unwrap() as u64;
+            let id = row.first().unwrap().as_integer().unwrap() as u64;
             let time = row.get(1).unwrap().as_integer().unwrap();
             let date = OffsetDateTime::from_unix_timestamp(time).unwrap();
             result.push(Unban { id, date });
@@ -160,7 +329,7 @@ impl Database {
         tracing::info!("Fetching scrim bans");
         let mut result = Vec::new();
         self.fetch_rows("ScheduledScrimUnbans", "", |row| {
-            let id = row.get(0).unwrap().as_integer().unwrap() as u64;
+            let id = row.first().unwrap().as_integer().unwrap() as u64;
             let time = row.get(1).unwrap().as_integer().unwrap();
             let date = OffsetDateTime::from_unix_timestamp(time).unwrap();
             let roles = Ids::try_from(row.get(2).unwrap().as_string().unwrap().to_owned()).unwrap();
@@ -170,10 +339,230 @@ impl Database {
         result
     }
 
+    /// The timeouts that have not ended yet
+    pub fn fetch_timeouts(&self) -> Vec<Untimeout> {
+        let mut result = Vec::new();
+        self.fetch_rows(
+            "Timeouts",
+            &format!(
+                "where time > {}",
+                OffsetDateTime::now_utc().unix_timestamp()
+            ),
+            |row| {
+                let id = row[0].as_integer().unwrap() as u64;
+                let date =
+                    OffsetDateTime::from_unix_timestamp(row[1].as_integer().unwrap()).unwrap();
+                result.push(Untimeout { id, date });
+            },
+        );
+        result
+    }
+
+    /// Counts the actions of every staff member since `since`
+    pub fn fetch_mod_stats(&self, since: OffsetDateTime) -> HashMap<u64, ModStats> {
+        let since = since.unix_timestamp();
+        let mut result: HashMap<u64, ModStats> = HashMap::new();
+        let queries = [
+            format!(
+                "SELECT staff, kind, count(*) FROM 'Punishments' WHERE staff IS NOT NULL AND created_at >= {} GROUP BY staff, kind",
+                since
+            ),
+            format!(
+                "SELECT creator, 'note', count(*) FROM 'Notes' WHERE created_at >= {} GROUP BY creator",
+                since
+            ),
+            format!(
+                "SELECT closer, 'close', count(*) FROM 'Screenshares' WHERE closer IS NOT NULL AND closed_at >= {} GROUP BY closer",
+                since
+            ),
+            format!(
+                "SELECT closer, 'close', count(*) FROM 'Tickets' WHERE closer IS NOT NULL AND closed_at >= {} GROUP BY closer",
+                since
+            ),
+            format!(
+                "SELECT staff, 'purge', count(*) FROM 'Purges' WHERE created_at >= {} GROUP BY staff",
+                since
+            ),
+        ];
+        let _lock = self.get_lock(|db| {
+            for query in &queries {
+                let mut cursor = db.prepare(query)?.into_cursor();
+                while let Ok(Some(row)) = cursor.next() {
+                    let (staff, kind, count) = match (&row[0], &row[1], &row[2]) {
+                        (
+                            sqlite::Value::Integer(staff),
+                            sqlite::Value::String(kind),
+                            sqlite::Value::Integer(count),
+                        ) => (*staff as u64, kind.as_str(), *count as u64),
+                        _ => continue,
+                    };
+                    let stats = result.entry(staff).or_default();
+                    match kind {
+                        "ban" => stats.bans += count,
+                        "scrimban" => stats.scrim_bans += count,
+                        "timeout" => stats.timeouts += count,
+                        "warning" => stats.warnings += count,
+                        "freeze" => stats.freezes += count,
+                        "note" => stats.notes += count,
+                        "close" => stats.tickets_closed += count,
+                        "purge" => stats.purges += count,
+                        _ => {}
+                    }
+                }
+            }
+            Ok(())
+        });
+        result
+    }
+
+    pub fn fetch_temp_roles(&self) -> Vec<TempRole> {
+        let mut result = Vec::new();
+        self.fetch_rows("TempRoles", "order by time", |row| {
+            result.push(TempRole {
+                user: row[0].as_integer().unwrap() as u64,
+                role: row[1].as_integer().unwrap() as u64,
+                date: OffsetDateTime::from_unix_timestamp(row[2].as_integer().unwrap()).unwrap(),
+            });
+        });
+        result
+    }
+
+    /// Fetches the open tickets, optionally only the ones of `creator`
+    pub fn fetch_tickets(&self, creator: Option<u64>) -> Vec<SupportTicket> {
+        let condition = match creator {
+            Some(creator) => format!("where closed_at is null and creator = {}", creator),
+            None => String::from("where closed_at is null"),
+        };
+        let mut result = Vec::new();
+        self.fetch_rows("Tickets", &condition, |row| {
+            result.push(SupportTicket {
+                id: row[0].as_integer().unwrap() as u64,
+                kind: row[1].as_string().unwrap_or_default().to_string(),
+                creator: row[2].as_integer().unwrap() as u64,
+            });
+        });
+        result
+    }
+
+    pub fn fetch_ticket(&self, id: u64) -> Option<SupportTicket> {
+        self.fetch_tickets(None).into_iter().find(|x| x.id == id)
+    }
+
+    /// Whether the ticket was opted out of the inactivity rules with `/ticket keepopen`
+    pub fn is_kept_open(&self, id: u64) -> bool {
+        let mut result = false;
+        self.fetch_rows("KeptOpen", &format!("where id = {}", id), |_| result = true);
+        result
+    }
+
+    /// Archived transcripts matching every given filter, the most recent first. `keyword` is
+    /// searched in the channel name and the messages.
+    pub fn fetch_transcripts(
+        &self,
+        user: Option<u64>,
+        keyword: Option<&str>,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> Vec<ArchivedTranscript> {
+        let mut conditions = Vec::new();
+        if let Some(user) = user {
+            conditions.push(format!("',' || participants || ',' LIKE '%,{},%'", user));
+        }
+        if let Some(keyword) = keyword {
+            let keyword = keyword
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+                .replace('\'', "''");
+            conditions.push(format!(
+                "(title LIKE '%{0}%' ESCAPE '\\' OR content LIKE '%{0}%' ESCAPE '\\')",
+                keyword
+            ));
+        }
+        if let Some(from) = from {
+            conditions.push(format!("closed_at >= {}", from.unix_timestamp()));
+        }
+        if let Some(to) = to {
+            conditions.push(format!("closed_at < {}", to.unix_timestamp()));
+        }
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("where {}", conditions.join(" AND "))
+        };
+        let mut result = Vec::new();
+        self.fetch_rows(
+            "Transcripts",
+            &format!("{} order by closed_at desc", condition),
+            |row| {
+                result.push(ArchivedTranscript {
+                    channel: row[0].as_integer().unwrap() as u64,
+                    title: row[1].as_string().unwrap_or_default().to_string(),
+                    participants: Ids::try_from(row[2].as_string().unwrap_or_default().to_string())
+                        .map(|x| x.0)
+                        .unwrap_or_default(),
+                    outcome: row[3].as_string().and_then(|x| x.parse().ok()),
+                    closed_at: OffsetDateTime::from_unix_timestamp(row[4].as_integer().unwrap())
+                        .unwrap(),
+                });
+            },
+        );
+        result
+    }
+
+    /// All scheduled jobs, the next due job first
+    pub fn fetch_jobs(&self) -> Vec<ScheduledJob> {
+        let mut result = Vec::new();
+        self.fetch_rows("ScheduledJobs", "order by time", |row| {
+            let kind = match row[1].as_string().unwrap().parse() {
+                Ok(kind) => kind,
+                Err(_) => return,
+            };
+            result.push(ScheduledJob {
+                id: row[0].as_integer().unwrap() as u64,
+                kind,
+                target: row[2].as_integer().unwrap() as u64,
+                data: row[3].as_string().unwrap().to_string(),
+                date: OffsetDateTime::from_unix_timestamp(row[4].as_integer().unwrap()).unwrap(),
+                attempts: row[5].as_integer().unwrap_or_default() as u64,
+            });
+        });
+        result
+    }
+
+    pub fn fetch_channel_reverts(&self) -> Vec<ChannelRevert> {
+        let mut result = Vec::new();
+        self.fetch_rows("ScheduledReverts", "", |row| {
+            let change = match row[1].as_string().unwrap().parse() {
+                Ok(change) => change,
+                Err(_) => return,
+            };
+            result.push(ChannelRevert {
+                channel: row[0].as_integer().unwrap() as u64,
+                change,
+                previous: row[2].as_string().unwrap().to_string(),
+                date: row[3]
+                    .as_integer()
+                    .and_then(|x| OffsetDateTime::from_unix_timestamp(x).ok()),
+            });
+        });
+        result
+    }
+
+    pub fn fetch_channel_revert(
+        &self,
+        channel: u64,
+        change: ChannelChange,
+    ) -> Option<ChannelRevert> {
+        self.fetch_channel_reverts()
+            .into_iter()
+            .find(|x| x.channel == channel && x.change == change)
+    }
+
     pub fn fetch_custom_reactions(&self) -> Vec<CustomReaction> {
         let mut result = Vec::new();
         self.fetch_rows("Reaction", "", |row| {
-            let user = row.get(0).unwrap().as_integer().unwrap() as u64;
+            let user = row.first().unwrap().as_integer().unwrap() as u64;
             let emoji = row.get(1).unwrap().as_string().unwrap().to_string();
             let trigger = row.get(2).unwrap().as_string().unwrap().to_string();
 
@@ -189,7 +578,7 @@ impl Database {
     pub fn fetch_custom_reactions_for(&self, userid: u64) -> Vec<CustomReaction> {
         let mut result = Vec::new();
         self.fetch_rows("Reaction", &format!("where user = {}", userid), |row| {
-            let user = row.get(0).unwrap().as_integer().unwrap() as u64;
+            let user = row.first().unwrap().as_integer().unwrap() as u64;
             let emoji = row.get(1).unwrap().as_string().unwrap().to_string();
             let trigger = row.get(2).unwrap().as_string().unwrap().to_string();
 
@@ -208,7 +597,7 @@ impl Database {
             "Reaction",
             &format!("where trigger = '{}'", trigger),
             |row| {
-                let user = row.get(0).unwrap().as_integer().unwrap() as u64;
+                let user = row.first().unwrap().as_integer().unwrap() as u64;
                 let emoji = row.get(1).unwrap().as_string().unwrap().to_string();
                 let trigger = row.get(2).unwrap().as_string().unwrap().to_string();
 
@@ -225,7 +614,7 @@ impl Database {
     pub fn fetch_notes_for(&self, userid: u64) -> Vec<Note> {
         let mut result = Vec::new();
         self.fetch_rows("Notes", &format!("where userid = {}", userid), |row| {
-            let userid = row.get(0).unwrap().as_integer().unwrap() as u64;
+            let userid = row.first().unwrap().as_integer().unwrap() as u64;
             let id = row.get(1).unwrap().as_integer().unwrap() as u64;
             let time = row.get(2).unwrap().as_integer().unwrap();
             let created_at = OffsetDateTime::from_unix_timestamp(time).unwrap();
@@ -243,15 +632,193 @@ impl Database {
         result
     }
 
-    pub fn fetch_screenshares_for(&self, id: u64) -> Option<Screenshare> {
-        let mut result = None;
-        self.fetch_rows("Screenshares", &format!("where id = {}", id), |row| {
-            let creator = row[1].as_integer().unwrap() as u64;
-            let in_question = row[2].as_integer().unwrap() as u64;
-            result.get_or_insert(Screenshare {
+    pub fn fetch_warnings_for(&self, userid: u64) -> Vec<Warning> {
+        let mut result = Vec::new();
+        self.fetch_rows("Warnings", &format!("where userid = {}", userid), |row| {
+            let id = row[1].as_integer().unwrap() as u64;
+            let created_at =
+                OffsetDateTime::from_unix_timestamp(row[2].as_integer().unwrap()).unwrap();
+            let reason = row[3].as_string().unwrap().to_string();
+            let creator = row[4].as_integer().unwrap() as u64;
+            let expires_at = row[5]
+                .as_integer()
+                .and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok());
+
+            result.push(Warning {
                 id,
+                created_at,
+                reason,
                 creator,
-                in_question,
+                expires_at,
+            });
+        });
+        result
+    }
+
+    fn punishment_from_row(row: &[sqlite::Value]) -> Option<Punishment> {
+        Some(Punishment {
+            id: row[0].as_integer()? as u64,
+            kind: row[1].as_string()?.parse().ok()?,
+            user: row[2].as_integer()? as u64,
+            staff: row[3].as_integer().map(|x| x as u64),
+            reason: row[4].as_string().unwrap_or_default().to_string(),
+            created_at: OffsetDateTime::from_unix_timestamp(row[5].as_integer()?).ok()?,
+            expires_at: row[6]
+                .as_integer()
+                .and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok()),
+        })
+    }
+
+    pub fn fetch_punishment(&self, id: u64) -> Option<Punishment> {
+        let mut result = None;
+        self.fetch_rows("Punishments", &format!("where id = {}", id), |row| {
+            result = Self::punishment_from_row(row);
+        });
+        result
+    }
+
+    pub fn fetch_punishments_for(&self, user: u64) -> Vec<Punishment> {
+        let mut result = Vec::new();
+        self.fetch_rows(
+            "Punishments",
+            &format!("where user = {} order by id", user),
+            |row| result.extend(Self::punishment_from_row(row)),
+        );
+        result
+    }
+
+    fn screenshare_from_row(row: &[sqlite::Value]) -> Screenshare {
+        let timestamp = |value: &sqlite::Value| {
+            value
+                .as_integer()
+                .and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
+        };
+        Screenshare {
+            id: row[0].as_integer().unwrap() as u64,
+            creator: row[1].as_integer().unwrap() as u64,
+            in_question: row[2].as_integer().unwrap() as u64,
+            created_at: timestamp(&row[3]),
+            closed_at: timestamp(&row[4]),
+            closer: row[5].as_integer().map(|x| x as u64),
+            deadline: timestamp(&row[6]),
+            claimer: row[7].as_integer().map(|x| x as u64),
+            outcome: row[8].as_string().and_then(|x| x.parse().ok()),
+        }
+    }
+
+    /// Fetches the open screenshare ticket in the given channel
+    pub fn fetch_screenshares_for(&self, id: u64) -> Option<Screenshare> {
+        let mut result = None;
+        self.fetch_rows(
+            "Screenshares",
+            &format!("where id = {} and closed_at is null", id),
+            |row| {
+                result.get_or_insert(Self::screenshare_from_row(row));
+            },
+        );
+        result
+    }
+
+    pub fn fetch_open_screenshares(&self) -> Vec<Screenshare> {
+        let mut result = Vec::new();
+        self.fetch_rows("Screenshares", "where closed_at is null", |row| {
+            result.push(Self::screenshare_from_row(row))
+        });
+        result
+    }
+
+    /// Fetches every screenshare, open or closed, that the user created or was the subject of
+    pub fn fetch_screenshare_history_for(&self, user: u64) -> Vec<Screenshare> {
+        let mut result = Vec::new();
+        self.fetch_rows(
+            "Screenshares",
+            &format!("where creator = {} or in_question = {}", user, user),
+            |row| result.push(Self::screenshare_from_row(row)),
+        );
+        result
+    }
+
+    fn appeal_from_row(row: &[sqlite::Value]) -> Option<Appeal> {
+        Some(Appeal {
+            user: row[1].as_integer()? as u64,
+            kind: row[2].as_string()?.parse().ok()?,
+            case: row[3].as_integer().map(|x| x as u64),
+            status: row[6].as_string()?.parse().ok()?,
+        })
+    }
+
+    pub fn fetch_appeal(&self, id: u64) -> Option<Appeal> {
+        let mut result = None;
+        self.fetch_rows("Appeals", &format!("where id = {}", id), |row| {
+            result = Self::appeal_from_row(row);
+        });
+        result
+    }
+
+    pub fn fetch_pending_escalation(&self, id: u64) -> Option<PendingEscalation> {
+        let mut result = None;
+        self.fetch_rows("PendingEscalations", &format!("where id = {}", id), |row| {
+            result = Some(PendingEscalation {
+                user: row[1].as_integer().unwrap() as u64,
+                rule: row[2].as_string().unwrap_or_default().to_string(),
+            });
+        });
+        result
+    }
+
+    pub fn fetch_open_appeal(&self, user: u64, kind: PunishmentKind) -> Option<Appeal> {
+        let mut result = None;
+        self.fetch_rows(
+            "Appeals",
+            &format!(
+                "where user = {} and kind = '{}' and status = '{}'",
+                user,
+                kind.as_str(),
+                AppealStatus::Open.as_str()
+            ),
+            |row| {
+                result = Self::appeal_from_row(row);
+            },
+        );
+        result
+    }
+
+    /// The Minecraft UUIDs that have been seen for a user
+    pub fn fetch_links_for(&self, userid: u64) -> Vec<String> {
+        let mut result = Vec::new();
+        self.fetch_rows(
+            "AccountLinks",
+            &format!("where userid = {}", userid),
+            |row| {
+                result.push(row[1].as_string().unwrap_or_default().to_string());
+            },
+        );
+        result
+    }
+
+    /// The users that have been seen with a Minecraft UUID
+    pub fn fetch_users_linked_to(&self, uuid: &str) -> Vec<u64> {
+        let mut result = Vec::new();
+        self.fetch_rows("AccountLinks", &format!("where uuid = '{}'", uuid), |row| {
+            result.push(row[0].as_integer().unwrap() as u64);
+        });
+        result
+    }
+
+    /// The users that joined during the last raid
+    pub fn fetch_raid_members(&self) -> Vec<u64> {
+        let mut result = Vec::new();
+        self.fetch_rows("RaidMembers", "order by joined_at", |row| {
+            result.push(row[0].as_integer().unwrap() as u64);
+        });
+        result
+    }
+
+    pub fn fetch_raid_mode(&self) -> Option<RaidMode> {
+        let mut result = None;
+        self.fetch_rows("RaidMode", "", |row| {
+            result = Some(RaidMode {
+                previous_level: row[1].as_integer().map(|x| x as u64),
             });
         });
         result
@@ -282,6 +849,18 @@ impl Database {
         })
     }
 
+    /// Stores a timeout, replacing any earlier timeout of the user
+    pub fn add_timeout(&self, id: u64, until: OffsetDateTime) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!("DELETE FROM 'Timeouts' WHERE id = {}", id))?;
+            db.execute(format!(
+                "INSERT INTO 'Timeouts' (id,time) values ({},{})",
+                id,
+                until.unix_timestamp()
+            ))
+        })
+    }
+
     pub fn modify_unban_date(
         &self,
         table: &str,
@@ -298,6 +877,22 @@ impl Database {
         })
     }
 
+    pub fn update_punishment(
+        &self,
+        id: u64,
+        reason: &str,
+        expires_at: Option<OffsetDateTime>,
+    ) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "UPDATE 'Punishments' SET reason = '{}', expires_at = {} WHERE id = {}",
+                reason.replace('\'', "''"),
+                expires_at.map_or_else(|| "NULL".to_string(), |x| x.unix_timestamp().to_string()),
+                id,
+            ))
+        })
+    }
+
     pub fn add_custom_reaction(
         &self,
         id: u64,
@@ -363,11 +958,186 @@ impl Database {
         })
     }
 
-    pub fn add_screenshare(&self, id: u64, creator: u64, in_question: u64) -> SqliteResult {
+    pub fn add_warning(
+        &self,
+        userid: u64,
+        created_at: OffsetDateTime,
+        reason: &str,
+        creator: u64,
+        expires_at: Option<OffsetDateTime>,
+    ) -> SqliteResult<i64> {
+        let mut count: Option<i64> = None;
+        self.count_rows("Warnings", &format!("where userid = {}", userid), |val| {
+            if let sqlite::Value::Integer(co) = val[0] {
+                count = Some(co);
+            }
+        });
+        let count = count.unwrap_or_default();
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'Warnings' (userid,id,created_at,reason,creator,expires_at) values ({},{},{},'{}',{},{})",
+                userid,
+                count + 1,
+                created_at.unix_timestamp(),
+                reason.replace('\'', "''"),
+                creator,
+                expires_at.map_or_else(|| "NULL".to_string(), |x| x.unix_timestamp().to_string()),
+            ))
+            .map(|_| count + 1)
+        })
+    }
+
+    /// Records a punishment and returns its case number
+    pub fn add_punishment(
+        &self,
+        kind: PunishmentKind,
+        user: u64,
+        staff: Option<u64>,
+        reason: &str,
+        expires_at: Option<OffsetDateTime>,
+    ) -> SqliteResult<i64> {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'Punishments' (kind,user,staff,reason,created_at,expires_at) values ('{}',{},{},'{}',{},{})",
+                kind.as_str(),
+                user,
+                staff.map_or_else(|| "NULL".to_string(), |x| x.to_string()),
+                reason.replace('\'', "''"),
+                OffsetDateTime::now_utc().unix_timestamp(),
+                expires_at.map_or_else(|| "NULL".to_string(), |x| x.unix_timestamp().to_string()),
+            ))?;
+            let mut cursor = db.prepare("select last_insert_rowid()")?.into_cursor();
+            let id = cursor
+                .next()?
+                .and_then(|row| row[0].as_integer())
+                .unwrap_or_default();
+            Ok(id)
+        })
+    }
+
+    pub fn add_appeal(
+        &self,
+        user: u64,
+        kind: PunishmentKind,
+        case: Option<u64>,
+        text: &str,
+    ) -> SqliteResult<i64> {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'Appeals' (user,kind,case_id,text,created_at,status) values ({},'{}',{},'{}',{},'{}')",
+                user,
+                kind.as_str(),
+                case.map_or_else(|| "NULL".to_string(), |x| x.to_string()),
+                // The appeal is written by the banned user, so it may contain anything
+                text.replace('\'', "''"),
+                OffsetDateTime::now_utc().unix_timestamp(),
+                AppealStatus::Open.as_str(),
+            ))?;
+            let mut cursor = db.prepare("select last_insert_rowid()")?.into_cursor();
+            let id = cursor
+                .next()?
+                .and_then(|row| row[0].as_integer())
+                .unwrap_or_default();
+            Ok(id)
+        })
+    }
+
+    pub fn add_pending_escalation(&self, user: u64, rule: &str) -> SqliteResult<i64> {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'PendingEscalations' (user,rule,created_at) values ({},'{}',{})",
+                user,
+                rule.replace('\'', "''"),
+                OffsetDateTime::now_utc().unix_timestamp(),
+            ))?;
+            let mut cursor = db.prepare("select last_insert_rowid()")?.into_cursor();
+            let id = cursor
+                .next()?
+                .and_then(|row| row[0].as_integer())
+                .unwrap_or_default();
+            Ok(id)
+        })
+    }
+
+    pub fn set_appeal_status(&self, id: u64, status: AppealStatus, reviewer: u64) -> SqliteResult {
         self.get_lock(|db| {
             db.execute(format!(
-                "INSERT INTO 'Screenshares' (id,creator,in_question) values ({},{},{})",
-                id, creator, in_question
+                "UPDATE 'Appeals' SET status = '{}', reviewer = {} WHERE id = {}",
+                status.as_str(),
+                reviewer,
+                id
+            ))
+        })
+    }
+
+    pub fn add_screenshare(
+        &self,
+        id: u64,
+        creator: u64,
+        in_question: u64,
+        deadline: OffsetDateTime,
+    ) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'Screenshares' (id,creator,in_question,created_at,deadline) values ({},{},{},{},{})",
+                id,
+                creator,
+                in_question,
+                OffsetDateTime::now_utc().unix_timestamp(),
+                deadline.unix_timestamp()
+            ))
+        })
+    }
+
+    pub fn set_screenshare_claimer(&self, id: u64, claimer: Option<u64>) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "UPDATE 'Screenshares' SET claimer = {} WHERE id = {}",
+                claimer.map_or_else(|| "null".to_string(), |x| x.to_string()),
+                id
+            ))
+        })
+    }
+
+    /// Claims the screenshare unless someone else claimed it first, returning whether it was claimed
+    pub fn claim_screenshare(&self, id: u64, claimer: u64) -> SqliteResult<bool> {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "UPDATE 'Screenshares' SET claimer = {} WHERE id = {} AND claimer IS NULL",
+                claimer, id
+            ))?;
+            let mut cursor = db.prepare("select changes()")?.into_cursor();
+            let changes = cursor
+                .next()?
+                .and_then(|row| row[0].as_integer())
+                .unwrap_or_default();
+            Ok(changes > 0)
+        })
+    }
+
+    /// Marks the freeze deadline of the screenshare as handled
+    pub fn clear_screenshare_deadline(&self, id: u64) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "UPDATE 'Screenshares' SET deadline = null WHERE id = {}",
+                id
+            ))
+        })
+    }
+
+    pub fn close_screenshare(
+        &self,
+        id: u64,
+        closer: Option<u64>,
+        outcome: Option<ScreenshareOutcome>,
+    ) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "UPDATE 'Screenshares' SET closed_at = {}, closer = {}, outcome = {} WHERE id = {}",
+                OffsetDateTime::now_utc().unix_timestamp(),
+                closer.map_or_else(|| "null".to_string(), |x| x.to_string()),
+                outcome.map_or_else(|| "null".to_string(), |x| format!("'{}'", x.as_str())),
+                id
             ))
         })
     }
@@ -396,6 +1166,232 @@ impl Database {
         })
     }
 
+    pub fn remove_warning(&self, userid: u64, id: u64) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "DELETE FROM 'Warnings' WHERE userid = {} AND id = {}",
+                userid, id
+            ))?;
+            db.execute(format!(
+                "UPDATE 'Warnings' SET id = id - 1 WHERE userid = {} AND id >= {}",
+                userid, id
+            ))
+        })
+    }
+
+    pub fn clear_warnings(&self, userid: u64) -> SqliteResult {
+        self.get_lock(|db| db.execute(format!("DELETE FROM 'Warnings' WHERE userid = {}", userid)))
+    }
+
+    /// Links a user to a Minecraft UUID, unless they already are
+    pub fn add_link(&self, userid: u64, uuid: &str, created_at: OffsetDateTime) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'AccountLinks' (userid,uuid,created_at) SELECT {0},'{1}',{2}
+                WHERE NOT EXISTS (SELECT 1 FROM 'AccountLinks' WHERE userid = {0} AND uuid = '{1}')",
+                userid,
+                uuid,
+                created_at.unix_timestamp()
+            ))
+        })
+    }
+
+    pub fn add_raid_member(&self, id: u64, joined_at: OffsetDateTime) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'RaidMembers' (id,joined_at) values ({},{})",
+                id,
+                joined_at.unix_timestamp()
+            ))
+        })
+    }
+
+    /// Stores the setting a channel change is reverted to, and when it is reverted automatically.
+    /// If a change is already stored the original setting is kept and only the time is updated.
+    pub fn add_channel_revert(
+        &self,
+        channel: u64,
+        change: ChannelChange,
+        previous: &str,
+        time: Option<OffsetDateTime>,
+    ) -> SqliteResult {
+        let time = time.map_or_else(|| "null".to_string(), |x| x.unix_timestamp().to_string());
+        if self.fetch_channel_revert(channel, change).is_some() {
+            return self.get_lock(|db| {
+                db.execute(format!(
+                    "UPDATE 'ScheduledReverts' SET time = {} WHERE channel = {} AND change = '{}'",
+                    time,
+                    channel,
+                    change.as_str()
+                ))
+            });
+        }
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'ScheduledReverts' (channel,change,previous,time) values ({},'{}','{}',{})",
+                channel,
+                change.as_str(),
+                previous,
+                time
+            ))
+        })
+    }
+
+    pub fn remove_channel_revert(&self, channel: u64, change: ChannelChange) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "DELETE FROM 'ScheduledReverts' WHERE channel = {} AND change = '{}'",
+                channel,
+                change.as_str()
+            ))
+        })
+    }
+
+    pub fn add_ticket(&self, id: u64, kind: &str, creator: u64) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'Tickets' (id,kind,creator,created_at) values ({},'{}',{},{})",
+                id,
+                kind.replace('\'', "''"),
+                creator,
+                OffsetDateTime::now_utc().unix_timestamp()
+            ))
+        })
+    }
+
+    pub fn close_support_ticket(&self, id: u64, closer: Option<u64>) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "UPDATE 'Tickets' SET closed_at = {}, closer = {} WHERE id = {}",
+                OffsetDateTime::now_utc().unix_timestamp(),
+                closer.map_or_else(|| "null".to_string(), |x| x.to_string()),
+                id
+            ))
+        })
+    }
+
+    pub fn set_kept_open(&self, id: u64, keep_open: bool) -> SqliteResult {
+        if !keep_open {
+            return self.remove_entry("KeptOpen", id);
+        }
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT OR REPLACE INTO 'KeptOpen' (id) values ({})",
+                id
+            ))
+        })
+    }
+
+    /// Stores a transcript, `content` is the plain text that is searched
+    pub fn add_transcript(&self, transcript: &ArchivedTranscript, content: &str) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT OR REPLACE INTO 'Transcripts' (channel,title,participants,outcome,closed_at,content) values ({},'{}','{}',{},{},'{}')",
+                transcript.channel,
+                transcript.title.replace('\'', "''"),
+                Ids(transcript.participants.clone()),
+                transcript
+                    .outcome
+                    .map_or_else(|| "null".to_string(), |x| format!("'{}'", x.as_str())),
+                transcript.closed_at.unix_timestamp(),
+                content.replace('\'', "''")
+            ))
+        })
+    }
+
+    pub fn add_purge(
+        &self,
+        staff: u64,
+        channel: u64,
+        count: u64,
+        created_at: OffsetDateTime,
+    ) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'Purges' (staff,channel,count,created_at) values ({},{},{},{})",
+                staff,
+                channel,
+                count,
+                created_at.unix_timestamp()
+            ))
+        })
+    }
+
+    /// Stores a temporary role, replacing the expiry if the user already has it
+    pub fn add_temp_role(&self, user: u64, role: u64, time: OffsetDateTime) -> SqliteResult {
+        self.remove_temp_role(user, role)?;
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT INTO 'TempRoles' (userid,role,time) values ({},{},{})",
+                user,
+                role,
+                time.unix_timestamp()
+            ))
+        })
+    }
+
+    pub fn remove_temp_role(&self, user: u64, role: u64) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "DELETE FROM 'TempRoles' WHERE userid = {} AND role = {}",
+                user, role
+            ))
+        })
+    }
+
+    /// Schedules a job, replacing the job with the same kind, target and data
+    pub fn add_job(
+        &self,
+        kind: JobKind,
+        target: u64,
+        data: &str,
+        time: OffsetDateTime,
+        attempts: u64,
+    ) -> SqliteResult {
+        let data = data.replace('\'', "''");
+        self.get_lock(|db| {
+            db.execute(format!(
+                "DELETE FROM 'ScheduledJobs' WHERE kind = '{}' AND target = {} AND data = '{}'",
+                kind.as_str(),
+                target,
+                data
+            ))?;
+            db.execute(format!(
+                "INSERT INTO 'ScheduledJobs' (kind,target,data,time,attempts) values ('{}',{},'{}',{},{})",
+                kind.as_str(),
+                target,
+                data,
+                time.unix_timestamp(),
+                attempts
+            ))
+        })
+    }
+
+    pub fn remove_job(&self, kind: JobKind, target: u64, data: &str) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "DELETE FROM 'ScheduledJobs' WHERE kind = '{}' AND target = {} AND data = '{}'",
+                kind.as_str(),
+                target,
+                data.replace('\'', "''")
+            ))
+        })
+    }
+
+    /// Starts raid mode, or updates the stored verification level if it is already active
+    pub fn set_raid_mode(&self, previous_level: Option<u64>) -> SqliteResult {
+        self.get_lock(|db| {
+            db.execute(format!(
+                "INSERT OR REPLACE INTO 'RaidMode' (id,previous_level) values (0,{})",
+                previous_level.map_or_else(|| "null".to_string(), |x| x.to_string())
+            ))
+        })
+    }
+
+    pub fn clear_raid_members(&self) -> SqliteResult {
+        self.get_lock(|db| db.execute("DELETE FROM 'RaidMembers'"))
+    }
+
     pub fn remove_entry(&self, table: &str, i: u64) -> SqliteResult {
         self.get_lock(|db| db.execute(format!("DELETE FROM '{}' WHERE id = {}", table, i)))
     }
@@ -416,7 +1412,7 @@ impl Database {
     pub fn get_screensharers(&self) -> Vec<Screensharer> {
         let mut result = Vec::new();
         self.fetch_rows("ScreensharerStats", "", |screensharer| {
-            let id = screensharer.get(0).unwrap().as_integer().unwrap() as u64;
+            let id = screensharer.first().unwrap().as_integer().unwrap() as u64;
             let freezes = screensharer.get(1).unwrap().as_integer().unwrap() as u64;
             result.push(Screensharer { id, freezes });
         });
@@ -430,7 +1426,7 @@ impl Database {
             "ScreensharerStats",
             &format!("where id = {}", user),
             |screensharer| {
-                let id = screensharer.get(0).unwrap().as_integer().unwrap() as u64;
+                let id = screensharer.first().unwrap().as_integer().unwrap() as u64;
                 let freezes = screensharer.get(1).unwrap().as_integer().unwrap() as u64;
                 let _ = result.get_or_insert(Screensharer { id, freezes });
             },
@@ -0,0 +1,283 @@
+//! Escalating punishments, configured in the `[escalation]` section of the config.
+//!
+//! Whenever a punishment is recorded, every rule that counts that kind of punishment is checked.
+//! A rule fires once the count reaches its threshold, after which the bot bans the user itself or
+//! asks staff in `support_bans` to confirm the ban first.
+
+use std::time::Duration;
+
+use bridge_scrims::duration::format_duration;
+use futures::future::{BoxFuture, FutureExt};
+use serenity::{
+    async_trait,
+    client::Context,
+    model::{
+        id::{MessageId, UserId},
+        interactions::{
+            message_component::{ButtonStyle, MessageComponentInteraction},
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+        },
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use crate::commands::ban::BanType;
+use crate::commands::Button;
+use crate::consts::{EscalationAction, EscalationRule, CONFIG, DATABASE};
+use crate::db::{JobKind, PunishmentKind};
+
+/// How long staff have to confirm an escalation
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Checks the rules that count `kind` after the user received such a punishment
+pub fn check(ctx: Context, user: UserId, kind: PunishmentKind) -> BoxFuture<'static, ()> {
+    async move {
+        for rule in CONFIG.escalation.rules.iter().filter(|x| x.count == kind) {
+            if count(rule, user) != rule.threshold {
+                continue;
+            }
+            tracing::info!("Escalation rule {} fired for {}", rule.name, user);
+            if rule.confirm {
+                tokio::spawn(confirm(ctx.clone(), user, rule));
+            } else if let Err(e) = escalate(ctx.clone(), user, rule, None).await {
+                tracing::error!(
+                    "Could not apply escalation {} to {}: {}",
+                    rule.name,
+                    user,
+                    e
+                );
+            }
+        }
+    }
+    .boxed()
+}
+
+fn count(rule: &EscalationRule, user: UserId) -> usize {
+    let since = rule
+        .within
+        .map(|within| OffsetDateTime::now_utc() - Duration::from_secs(within));
+    let recent = |date: OffsetDateTime| since.is_none_or(|since| date >= since);
+    match rule.count {
+        // Warnings can expire or be removed, so they are counted from their own table
+        PunishmentKind::Warning => DATABASE
+            .fetch_warnings_for(user.0)
+            .iter()
+            .filter(|x| x.is_active() && recent(x.created_at))
+            .count(),
+        kind => DATABASE
+            .fetch_punishments_for(user.0)
+            .iter()
+            .filter(|x| x.kind == kind && recent(x.created_at))
+            .count(),
+    }
+}
+
+fn ban_type(rule: &EscalationRule) -> BanType {
+    match rule.action {
+        EscalationAction::Ban => BanType::Server,
+        EscalationAction::ScrimBan => BanType::Scrim,
+    }
+}
+
+fn duration(rule: &EscalationRule) -> Option<Duration> {
+    rule.duration
+        .map(Duration::from_secs)
+        .or_else(|| ban_type(rule).default_duration())
+}
+
+fn describe(rule: &EscalationRule) -> String {
+    let action = match rule.action {
+        EscalationAction::Ban => "server ban",
+        EscalationAction::ScrimBan => "scrim ban",
+    };
+    match duration(rule) {
+        Some(duration) => format!("{} {}", format_duration(duration), action),
+        None => format!("permanent {}", action),
+    }
+}
+
+async fn escalate(
+    ctx: Context,
+    user: UserId,
+    rule: &EscalationRule,
+    staff: Option<UserId>,
+) -> serenity::Result<()> {
+    let target = user.to_user(&ctx.http).await?;
+    let ban_type = ban_type(rule);
+    let unban_date = duration(rule).map(|duration| OffsetDateTime::now_utc() + duration);
+    let outcome = ban_type
+        .apply(
+            &ctx.http,
+            &ctx.cache,
+            &target,
+            staff,
+            format!("Escalation: {}", rule.name),
+            unban_date,
+            0,
+        )
+        .await;
+    if let Err(e) = outcome.result {
+        CONFIG
+            .support_bans
+            .send_message(&ctx.http, |msg| {
+                msg.embed(|embed| {
+                    embed
+                        .title("Escalation failed")
+                        .field("User", format!("<@{}>", user), false)
+                        .field("Rule", &rule.name, false)
+                        .field("Action", describe(rule), false)
+                        .field("Error", e.to_string(), false)
+                        .color(Color::DARK_RED)
+                })
+            })
+            .await?;
+        return Err(e);
+    }
+    // The ban itself may fire the next step of the ladder
+    check(ctx, user, ban_type.punishment_kind()).await;
+    Ok(())
+}
+
+async fn confirm(ctx: Context, user: UserId, rule: &'static EscalationRule) {
+    if let Err(e) = request_confirmation(&ctx, user, rule).await {
+        tracing::error!(
+            "Could not ask for confirmation of escalation {} for {}: {}",
+            rule.name,
+            user,
+            e
+        );
+    }
+}
+
+async fn request_confirmation(
+    ctx: &Context,
+    user: UserId,
+    rule: &'static EscalationRule,
+) -> crate::Result<()> {
+    let id = DATABASE.add_pending_escalation(user.0, &rule.name)?;
+    let message = CONFIG
+        .support_bans
+        .send_message(&ctx.http, |msg| {
+            msg.embed(|embed| {
+                embed
+                    .title("Escalation requires confirmation")
+                    .field("User", format!("<@{}>", user), false)
+                    .field("Rule", &rule.name, false)
+                    .field("Action", describe(rule), false)
+                    .color(Color::ORANGE)
+            })
+            .components(|c| {
+                c.create_action_row(|row| {
+                    row.create_button(|b| {
+                        b.label("Confirm")
+                            .style(ButtonStyle::Danger)
+                            .custom_id(format!("escalation:confirm:{}", id))
+                    })
+                    .create_button(|b| {
+                        b.label("Dismiss")
+                            .style(ButtonStyle::Secondary)
+                            .custom_id(format!("escalation:dismiss:{}", id))
+                    })
+                })
+            })
+        })
+        .await?;
+    crate::scheduler::schedule(
+        JobKind::EscalationExpiry,
+        id as u64,
+        &message.id.to_string(),
+        OffsetDateTime::now_utc() + CONFIRM_TIMEOUT,
+    );
+    Ok(())
+}
+
+/// Ends an escalation that was not confirmed in time
+pub async fn expire(ctx: &Context, id: u64, message: MessageId) -> serenity::Result<()> {
+    if DATABASE.fetch_pending_escalation(id).is_none() {
+        return Ok(());
+    }
+    let _ = DATABASE.remove_entry("PendingEscalations", id);
+    CONFIG
+        .support_bans
+        .edit_message(&ctx.http, message, |m| {
+            m.content("Expired without confirmation")
+                .components(|c| c.set_action_rows(Default::default()))
+        })
+        .await?;
+    Ok(())
+}
+
+/// Handles the buttons of escalations that need confirmation, `escalation:<confirm|dismiss>:<id>`
+pub struct Escalation;
+
+#[async_trait]
+impl Button for Escalation {
+    fn prefix(&self) -> String {
+        String::from("escalation")
+    }
+    async fn click(
+        &self,
+        ctx: &Context,
+        interaction: &MessageComponentInteraction,
+    ) -> crate::Result<()> {
+        let parts: Vec<&str> = interaction.data.custom_id.split(':').collect();
+        let (confirmed, id) = match parts.as_slice() {
+            [_, action, id] => (*action == "confirm", id.parse::<u64>()?),
+            _ => return Ok(()),
+        };
+        let allowed = interaction.member.as_ref().is_some_and(|member| {
+            member.roles.contains(&CONFIG.support) || member.roles.contains(&CONFIG.staff)
+        });
+        let pending = DATABASE.fetch_pending_escalation(id);
+        let rule = pending
+            .as_ref()
+            .and_then(|x| CONFIG.escalation.rules.iter().find(|r| r.name == x.rule));
+        let error = match (&pending, rule) {
+            _ if !allowed => Some("You are not allowed to confirm escalations."),
+            (None, _) => Some("This escalation was already handled."),
+            (Some(_), None) => Some("The rule of this escalation is no longer in the config."),
+            _ => None,
+        };
+        if let Some(error) = error {
+            interaction
+                .create_interaction_response(&ctx.http, |resp| {
+                    resp.interaction_response_data(|data| {
+                        data.content(error)
+                            .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                    })
+                })
+                .await?;
+            return Ok(());
+        }
+        DATABASE.remove_entry("PendingEscalations", id)?;
+        crate::scheduler::cancel(
+            JobKind::EscalationExpiry,
+            id,
+            &interaction.message.id.to_string(),
+        );
+        interaction
+            .create_interaction_response(&ctx.http, |resp| {
+                resp.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|data| {
+                        data.content(format!(
+                            "{} by <@{}>",
+                            if confirmed { "Confirmed" } else { "Dismissed" },
+                            interaction.user.id
+                        ))
+                        .components(|c| c.set_action_rows(Default::default()))
+                    })
+            })
+            .await?;
+        if let (true, Some(pending), Some(rule)) = (confirmed, pending, rule) {
+            escalate(
+                ctx.clone(),
+                UserId(pending.user),
+                rule,
+                Some(interaction.user.id),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
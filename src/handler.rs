@@ -2,26 +2,37 @@ use std::time::Duration;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::Mutex;
 
-use crate::commands::ban::{Ban, ScrimBan};
+use crate::appeal::Appeal;
+use crate::commands::ban::{Ban, Kick, ScrimBan, SoftBan};
+use crate::commands::case::Case;
 use crate::commands::close::Close;
 use crate::commands::council::Council;
 use crate::commands::freeze::Freeze;
+use crate::commands::history::History;
 use crate::commands::list_bans::ListBans;
+use crate::commands::lockdown::{Lockdown, Slowmode};
+use crate::commands::massban::{MassBan, MassScrimBan};
+use crate::commands::modstats::ModStats;
 use crate::commands::notes::Notes;
 use crate::commands::ping::Ping;
 use crate::commands::prefabs::Prefab;
 use crate::commands::purge::Purge;
+use crate::commands::raid::Raid;
 use crate::commands::reaction::{DelReaction, ListReactions, Reaction};
 use crate::commands::reload::Reload;
 use crate::commands::roll::{Roll, Teams};
 use crate::commands::screenshare::Screenshare;
 use crate::commands::screensharers::Screensharers;
-use crate::commands::ticket::Ticket;
-use crate::commands::timeout::Timeout;
+use crate::commands::temprole::TempRole;
+use crate::commands::ticket::{Ticket, TicketPanel};
+use crate::commands::timeout::{Timeout, Untimeout};
+use crate::commands::transcripts::Transcripts;
 use crate::commands::unban::{ScrimUnban, Unban};
 use crate::commands::Command as _;
+use crate::escalation::Escalation;
 
 use crate::commands::unfreeze::Unfreeze;
+use crate::commands::warn::Warn;
 use crate::consts::CONFIG;
 use crate::consts::DATABASE as database;
 use crate::db::CustomReaction;
@@ -33,8 +44,8 @@ use serenity::model::channel::{Message, MessageType, ReactionType};
 use serenity::model::gateway::Ready;
 use serenity::model::id::EmojiId;
 use serenity::model::interactions::{Interaction, InteractionApplicationCommandCallbackDataFlags};
-use serenity::prelude::Mentionable;
 use serenity::model::prelude::Member;
+use serenity::prelude::Mentionable;
 use serenity::utils::Color;
 
 use serenity::model::id::GuildId;
@@ -44,6 +55,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 type Command = Box<dyn crate::commands::Command>;
+type Button = Box<dyn crate::commands::Button>;
 
 lazy_static! {
     pub static ref COMMANDS: Vec<Command> = vec![
@@ -51,9 +63,18 @@ lazy_static! {
         Notes::new(),
         Prefab::new(),
         Timeout::new(),
+        Untimeout::new(),
         Ban::new(),
         Unban::new(),
         ScrimBan::new(),
+        Kick::new(),
+        SoftBan::new(),
+        MassBan::new(),
+        MassScrimBan::new(),
+        Lockdown::new(),
+        Slowmode::new(),
+        TempRole::new(),
+        ModStats::new(),
         ScrimUnban::new(),
         Roll::new(),
         Teams::new(),
@@ -66,10 +87,26 @@ lazy_static! {
         Freeze::new(),
         Unfreeze::new(),
         Ticket::new(),
+        TicketPanel::new(),
+        Transcripts::new(),
         ListBans::new(),
         Screensharers::new(),
         Reload::new(),
         Ping::new(),
+        Warn::new(),
+        Case::new(),
+        History::new(),
+        Raid::new(),
+    ];
+    pub static ref BUTTONS: Vec<Button> = vec![
+        Screenshare::new(),
+        Close::new(),
+        Freeze::new(),
+        Ticket::new(),
+        History::new(),
+        Raid::new(),
+        Box::new(Appeal),
+        Box::new(Escalation),
     ];
 }
 
@@ -98,11 +135,26 @@ impl EventHandler for Handler {
                 command.init(&ctx).await
             }
             tokio::spawn(update_reactions(self.reactions.clone()));
+            crate::scheduler::start(ctx.clone());
         }
         // Errors are already handled
         let _ = register_commands(&ctx).await;
     }
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        // Buttons and select menus only, serenity 0.10 does not know modal submits
+        if let Interaction::MessageComponent(ref component) = interaction {
+            let prefix = component
+                .data
+                .custom_id
+                .split(':')
+                .next()
+                .unwrap_or_default();
+            if let Some(button) = BUTTONS.iter().find(|x| x.prefix() == prefix) {
+                if let Err(err) = button.click(&ctx, component).await {
+                    tracing::error!("{} button failed: {}", prefix, err);
+                }
+            }
+        }
         if let Interaction::ApplicationCommand(command_interaction) = interaction {
             if let Some(command) = COMMANDS
                 .iter()
@@ -136,6 +188,9 @@ impl EventHandler for Handler {
         if msg.author.bot {
             return;
         }
+        if crate::automod::check(&ctx, &msg).await {
+            return;
+        }
         if msg
             .content
             .to_ascii_lowercase()
@@ -156,7 +211,7 @@ impl EventHandler for Handler {
                 tracing::error!("{}", err);
             }
         }
-        if msg.content.to_ascii_lowercase() == "ratio"
+        if msg.content.eq_ignore_ascii_case("ratio")
             || msg.content.to_ascii_lowercase().replace(' ', "") == "counterratio"
         {
             if let Err(err) = msg.react(&ctx, ReactionType::Unicode("👍".into())).await {
@@ -299,7 +354,10 @@ impl EventHandler for Handler {
         }
     }
 
-    async fn guild_member_addition(&self, ctx: Context, guild_id: GuildId, _member: Member) {
+    async fn guild_member_addition(&self, ctx: Context, guild_id: GuildId, member: Member) {
+        crate::raid::member_joined(&ctx, &member).await;
+        crate::alts::check(&ctx, member.user.id).await;
+        crate::scheduler::member_joined(member.user.id);
         if let Err(err) = CONFIG.member_count.update(ctx, guild_id).await {
             tracing::error!("Error when updating member count: {}", err)
         }
@@ -316,7 +374,10 @@ impl EventHandler for Handler {
         }
     }
 
-    async fn guild_member_update(&self, ctx: Context, _old_data: Option<Member>, user: Member) {
+    async fn guild_member_update(&self, ctx: Context, old_data: Option<Member>, user: Member) {
+        if old_data.is_none_or(|x| x.nick != user.nick) {
+            crate::alts::link_nickname(&ctx, &user).await;
+        }
         let mut x = false;
 
         for role in user.roles(&ctx.cache).await.unwrap() {
@@ -0,0 +1,49 @@
+//! Human readable durations
+
+use std::time::Duration;
+
+const UNITS: [(u64, &str); 4] = [
+    (86400, "day"),
+    (3600, "hour"),
+    (60, "minute"),
+    (1, "second"),
+];
+
+/// Formats a duration in its largest units, e.g. `1 day 2 hours`
+pub fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.as_secs();
+    let mut parts = Vec::new();
+    for (size, name) in UNITS {
+        let amount = seconds / size;
+        seconds %= size;
+        if amount > 0 {
+            parts.push(format!(
+                "{} {}{}",
+                amount,
+                name,
+                if amount == 1 { "" } else { "s" }
+            ));
+        }
+    }
+    if parts.is_empty() {
+        String::from("0 seconds")
+    } else {
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0 seconds");
+        assert_eq!(format_duration(Duration::from_secs(1)), "1 second");
+        assert_eq!(format_duration(Duration::from_secs(86400)), "1 day");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86400 + 3600 + 5)),
+            "2 days 1 hour 5 seconds"
+        );
+    }
+}
@@ -94,23 +94,18 @@ impl FromStr for UUID {
         if s.len() != 32 {
             return Err(ApiError::InvalidUUID);
         }
-        Ok(Self(s.bytes().enumerate().fold(
-            Ok([0u8; 16]),
-            |acc, (i, c)| {
-                let num = u8::from_str_radix(std::str::from_utf8(&[c]).unwrap(), 16);
-                if num.is_err() {
-                    return Err(ApiError::InvalidUUID);
+        Ok(Self(s.bytes().enumerate().try_fold(
+            [0u8; 16],
+            |mut acc, (i, c)| {
+                let mut num = u8::from_str_radix(std::str::from_utf8(&[c]).unwrap(), 16)
+                    .map_err(|_| ApiError::InvalidUUID)?;
+                if i % 2 == 0 {
+                    num <<= 4;
+                    acc[i / 2] = num;
+                } else {
+                    acc[i / 2] += num;
                 }
-                acc.map(|mut acc| {
-                    let mut num = num.unwrap();
-                    if i % 2 == 0 {
-                        num <<= 4;
-                        acc[i / 2] = num;
-                    } else {
-                        acc[i / 2] += num;
-                    }
-                    acc
-                })
+                Ok::<_, ApiError>(acc)
             },
         )?))
     }
@@ -1,4 +1,6 @@
 pub mod cooldown;
+pub mod duration;
 pub mod hypixel;
 pub mod interact_opts;
 pub mod print_embeds;
+pub mod transcript;
@@ -0,0 +1,527 @@
+//! This module renders messages into a self-contained HTML transcript
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use regex::{Captures, Regex};
+use serenity::model::{
+    channel::{Attachment, Embed, Message},
+    id::{ChannelId, RoleId, UserId},
+};
+
+lazy_static::lazy_static! {
+    /// Parts of a message that are rendered on their own: code, mentions, emojis, timestamps and
+    /// links
+    static ref TOKEN: Regex = Regex::new(concat!(
+        r"(?s:```(?:[a-zA-Z0-9_+-]+\n)?(?P<block>.*?)```)",
+        r"|`(?P<code>[^`]+)`",
+        r"|<@!?(?P<user>\d+)>",
+        r"|<@&(?P<role>\d+)>",
+        r"|<#(?P<channel>\d+)>",
+        r"|<(?P<animated>a?):(?P<emoji_name>\w+):(?P<emoji>\d+)>",
+        r"|<t:(?P<time>-?\d+)(?::[tTdDfFR])?>",
+        r"|(?P<url>https?://[^\s<>]+)",
+    ))
+    .unwrap();
+    static ref PLACEHOLDER: Regex = Regex::new("\u{0}(\\d+)\u{0}").unwrap();
+    static ref FORMATTING: Vec<(Regex, &'static str)> = vec![
+        (Regex::new(r"\*\*(.+?)\*\*").unwrap(), "<strong>$1</strong>"),
+        (Regex::new(r"__(.+?)__").unwrap(), "<u>$1</u>"),
+        (Regex::new(r"\*(.+?)\*").unwrap(), "<em>$1</em>"),
+        (Regex::new(r"~~(.+?)~~").unwrap(), "<s>$1</s>"),
+        (Regex::new(r"\|\|(.+?)\|\|").unwrap(), r#"<span class="spoiler">$1</span>"#),
+        (Regex::new(r"(?m)^&gt; (.*)$").unwrap(), "<blockquote>$1</blockquote>"),
+    ];
+}
+
+const STYLE: &str = "
+body { background: #36393f; color: #dcddde; font-family: sans-serif; margin: 0; padding: 16px; }
+h1 { color: #fff; font-size: 20px; margin: 0 0 4px; }
+.info { color: #a3a6aa; margin: 0 0 16px; }
+.message { display: flex; padding: 6px 0; }
+.avatar { width: 40px; height: 40px; border-radius: 50%; margin-right: 16px; flex-shrink: 0; }
+.body { min-width: 0; }
+.author { color: #fff; font-weight: bold; }
+.time, .edited, .reply { color: #a3a6aa; font-size: 12px; }
+.content { white-space: normal; overflow-wrap: anywhere; }
+.mention { background: #414675; color: #dee0fc; border-radius: 3px; padding: 0 2px; }
+.emoji { width: 22px; height: 22px; vertical-align: bottom; }
+.spoiler { background: #202225; color: #202225; }
+.spoiler:hover { color: inherit; }
+code, pre { background: #2f3136; border-radius: 4px; font-family: monospace; }
+pre { padding: 8px; white-space: pre-wrap; }
+blockquote { border-left: 4px solid #4f545c; margin: 0; padding-left: 8px; }
+a { color: #00aff4; }
+.attachment { display: block; max-width: 400px; max-height: 300px; margin-top: 4px; }
+.file { display: inline-block; background: #2f3136; border-radius: 4px; margin-top: 4px; padding: 8px; }
+.embed { background: #2f3136; border-left: 4px solid #202225; border-radius: 4px; margin-top: 4px; max-width: 520px; padding: 8px 12px; }
+.embed-title { color: #fff; font-weight: bold; }
+.embed-field-name { color: #fff; font-weight: bold; margin-top: 4px; }
+.embed-image { display: block; max-width: 400px; margin-top: 8px; }
+.embed-thumbnail { float: right; max-width: 80px; margin-left: 8px; }
+.embed-footer { color: #a3a6aa; font-size: 12px; margin-top: 8px; }
+";
+
+/// Names for mentions that can not be resolved from the messages themselves
+#[derive(Default)]
+pub struct Names {
+    pub roles: HashMap<RoleId, String>,
+    pub channels: HashMap<ChannelId, String>,
+}
+
+/// A transcript of `messages`, which are expected to be sorted from oldest to newest.
+pub struct Transcript<'a> {
+    pub title: String,
+    pub messages: &'a [Message],
+    pub names: Names,
+}
+
+impl Transcript<'_> {
+    /// The users mentioned in or sending any of the messages
+    fn users(&self) -> HashMap<UserId, String> {
+        let mut users = HashMap::new();
+        for message in self.messages {
+            let replied = message.referenced_message.iter().map(|x| &x.author);
+            for user in message
+                .mentions
+                .iter()
+                .chain(std::iter::once(&message.author))
+                .chain(replied)
+            {
+                users.insert(user.id, user.name.clone());
+            }
+        }
+        users
+    }
+
+    fn render_text(&self, text: &str, users: &HashMap<UserId, String>) -> String {
+        let mut tokens = Vec::new();
+        let text = text.replace('\u{0}', "");
+        let text = TOKEN.replace_all(&text, |caps: &Captures| {
+            tokens.push(self.render_token(caps, users));
+            format!("\u{0}{}\u{0}", tokens.len() - 1)
+        });
+        let mut html = escape(&text);
+        for (regex, replacement) in FORMATTING.iter() {
+            html = regex.replace_all(&html, *replacement).into_owned();
+        }
+        let html = html.replace('\n', "<br>");
+        PLACEHOLDER
+            .replace_all(&html, |caps: &Captures| {
+                tokens[caps[1].parse::<usize>().unwrap()].clone()
+            })
+            .into_owned()
+    }
+
+    fn render_token(&self, caps: &Captures, users: &HashMap<UserId, String>) -> String {
+        let id = |name: &str| caps.name(name).and_then(|x| x.as_str().parse::<u64>().ok());
+        let mention = |prefix: &str, name: Option<&String>, id: u64| {
+            format!(
+                r#"<span class="mention">{}{}</span>"#,
+                prefix,
+                escape(&name.cloned().unwrap_or_else(|| id.to_string()))
+            )
+        };
+        if let Some(block) = caps.name("block") {
+            format!(
+                "<pre>{}</pre>",
+                escape(block.as_str().trim_end_matches('\n'))
+            )
+        } else if let Some(code) = caps.name("code") {
+            format!("<code>{}</code>", escape(code.as_str()))
+        } else if let Some(user) = id("user") {
+            mention("@", users.get(&UserId(user)), user)
+        } else if let Some(role) = id("role") {
+            mention("@", self.names.roles.get(&RoleId(role)), role)
+        } else if let Some(channel) = id("channel") {
+            mention("#", self.names.channels.get(&ChannelId(channel)), channel)
+        } else if let Some(emoji) = id("emoji") {
+            let extension = if caps["animated"].is_empty() {
+                "png"
+            } else {
+                "gif"
+            };
+            format!(
+                r#"<img class="emoji" src="https://cdn.discordapp.com/emojis/{}.{}" alt=":{}:">"#,
+                emoji,
+                extension,
+                escape(&caps["emoji_name"])
+            )
+        } else if let Some(time) = caps.name("time") {
+            let date = time
+                .as_str()
+                .parse()
+                .ok()
+                .and_then(|x| DateTime::<Utc>::from_timestamp(x, 0));
+            match date {
+                Some(date) => format!(r#"<span class="mention">{}</span>"#, format_time(&date)),
+                None => escape(&caps[0]),
+            }
+        } else {
+            let url = escape(&caps[0]);
+            format!(r#"<a href="{0}">{0}</a>"#, url)
+        }
+    }
+
+    fn render_attachment(
+        f: &mut std::fmt::Formatter<'_>,
+        attachment: &Attachment,
+    ) -> std::fmt::Result {
+        let is_image = attachment.content_type.as_ref().map_or_else(
+            || {
+                let name = attachment.filename.to_ascii_lowercase();
+                [".png", ".jpg", ".jpeg", ".gif", ".webp"]
+                    .iter()
+                    .any(|x| name.ends_with(x))
+            },
+            |x| x.starts_with("image/"),
+        );
+        let url = escape(&attachment.url);
+        let name = escape(&attachment.filename);
+        if is_image {
+            writeln!(
+                f,
+                r#"<a href="{0}"><img class="attachment" src="{0}" alt="{1}"></a>"#,
+                url, name
+            )
+        } else {
+            writeln!(
+                f,
+                r#"<a class="file" href="{}">{} ({} KB)</a>"#,
+                url,
+                name,
+                attachment.size / 1024
+            )
+        }
+    }
+
+    fn render_embed(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        embed: &Embed,
+        users: &HashMap<UserId, String>,
+    ) -> std::fmt::Result {
+        writeln!(
+            f,
+            r#"<div class="embed" style="border-left-color: #{}">"#,
+            embed.colour.hex()
+        )?;
+        if let Some(thumbnail) = &embed.thumbnail {
+            writeln!(
+                f,
+                r#"<img class="embed-thumbnail" src="{}">"#,
+                escape(&thumbnail.url)
+            )?;
+        }
+        if let Some(author) = &embed.author {
+            writeln!(f, r#"<div class="author">{}</div>"#, escape(&author.name))?;
+        }
+        if let Some(title) = &embed.title {
+            match &embed.url {
+                Some(url) => writeln!(
+                    f,
+                    r#"<div class="embed-title"><a href="{}">{}</a></div>"#,
+                    escape(url),
+                    escape(title)
+                )?,
+                None => writeln!(f, r#"<div class="embed-title">{}</div>"#, escape(title))?,
+            }
+        }
+        if let Some(description) = &embed.description {
+            writeln!(f, "<div>{}</div>", self.render_text(description, users))?;
+        }
+        for field in &embed.fields {
+            writeln!(
+                f,
+                r#"<div class="embed-field-name">{}</div><div>{}</div>"#,
+                self.render_text(&field.name, users),
+                self.render_text(&field.value, users)
+            )?;
+        }
+        if let Some(image) = &embed.image {
+            writeln!(
+                f,
+                r#"<img class="embed-image" src="{}">"#,
+                escape(&image.url)
+            )?;
+        }
+        if let Some(footer) = &embed.footer {
+            writeln!(
+                f,
+                r#"<div class="embed-footer">{}</div>"#,
+                escape(&footer.text)
+            )?;
+        }
+        writeln!(f, "</div>")
+    }
+
+    fn render_message(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        message: &Message,
+        users: &HashMap<UserId, String>,
+    ) -> std::fmt::Result {
+        writeln!(f, r#"<div class="message" id="{}">"#, message.id)?;
+        writeln!(
+            f,
+            r#"<img class="avatar" src="{}" alt="">"#,
+            escape(&message.author.face())
+        )?;
+        writeln!(f, r#"<div class="body">"#)?;
+        if let Some(reply) = &message.referenced_message {
+            let mut snippet: String = reply.content.chars().take(100).collect();
+            if snippet.len() < reply.content.len() {
+                snippet.push_str("...");
+            }
+            writeln!(
+                f,
+                r##"<div class="reply"><a href="#{}">Reply to</a> <span class="author">{}</span> {}</div>"##,
+                reply.id,
+                escape(&reply.author.name),
+                self.render_text(&snippet, users)
+            )?;
+        }
+        write!(
+            f,
+            r#"<div><span class="author">{}</span> <span class="time">{}</span>"#,
+            escape(&message.author.tag()),
+            format_time(&message.timestamp)
+        )?;
+        if message.edited_timestamp.is_some() {
+            write!(f, r#" <span class="edited">(edited)</span>"#)?;
+        }
+        writeln!(f, "</div>")?;
+        if !message.content.is_empty() {
+            writeln!(
+                f,
+                r#"<div class="content">{}</div>"#,
+                self.render_text(&message.content, users)
+            )?;
+        }
+        for attachment in &message.attachments {
+            Self::render_attachment(f, attachment)?;
+        }
+        for embed in &message.embeds {
+            self.render_embed(f, embed, users)?;
+        }
+        writeln!(f, "</div>\n</div>")
+    }
+}
+
+impl Display for Transcript<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let users = self.users();
+        let title = escape(&self.title);
+        writeln!(f, "<!DOCTYPE html>")?;
+        writeln!(f, r#"<html><head><meta charset="utf-8">"#)?;
+        writeln!(f, "<title>{}</title>", title)?;
+        writeln!(f, "<style>{}</style>", STYLE)?;
+        writeln!(f, "</head><body>")?;
+        writeln!(f, "<h1>{}</h1>", title)?;
+        writeln!(f, r#"<p class="info">{} messages</p>"#, self.messages.len())?;
+        for message in self.messages {
+            self.render_message(f, message, &users)?;
+        }
+        writeln!(f, "</body></html>")
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(id: u64, content: &str, extra: serde_json::Value) -> Message {
+        let mut value = json!({
+            "id": id.to_string(),
+            "attachments": [],
+            "author": {
+                "id": "1",
+                "username": "Alice",
+                "discriminator": "0001",
+                "avatar": null
+            },
+            "channel_id": "10",
+            "content": content,
+            "edited_timestamp": null,
+            "embeds": [],
+            "type": 0,
+            "mention_everyone": false,
+            "mention_roles": [],
+            "mentions": [],
+            "pinned": false,
+            "timestamp": "2022-01-01T12:00:00+00:00",
+            "tts": false
+        });
+        for (key, extra) in extra.as_object().unwrap() {
+            value[key] = extra.clone();
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn render(messages: &[Message], names: Names) -> String {
+        Transcript {
+            title: String::from("screenshare-1"),
+            messages,
+            names,
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn escapes_html() {
+        let html = render(
+            &[message(1, "<script>alert('x')</script> & co", json!({}))],
+            Names::default(),
+        );
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; co"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn formats_markdown() {
+        let html = render(
+            &[message(
+                1,
+                "**bold** *it* __u__ ~~s~~ `<b>` ||x||",
+                json!({}),
+            )],
+            Names::default(),
+        );
+        assert!(html.contains(
+            r#"<strong>bold</strong> <em>it</em> <u>u</u> <s>s</s> <code>&lt;b&gt;</code> <span class="spoiler">x</span>"#
+        ));
+    }
+
+    #[test]
+    fn code_blocks_are_not_formatted() {
+        let html = render(
+            &[message(1, "```rust\n**a** <@2>\n```", json!({}))],
+            Names::default(),
+        );
+        assert!(html.contains("<pre>**a** &lt;@2&gt;</pre>"));
+    }
+
+    #[test]
+    fn resolves_mentions() {
+        let mut names = Names::default();
+        names.roles.insert(RoleId(3), String::from("Screensharer"));
+        names.channels.insert(ChannelId(4), String::from("general"));
+        let mentions = json!({
+            "mentions": [{"id": "2", "username": "Bob", "discriminator": "0002", "avatar": null}]
+        });
+        let html = render(
+            &[message(1, "**<@!2>** <@&3> <#4> <@5> <t:0:R>", mentions)],
+            names,
+        );
+        assert!(html.contains(r#"<strong><span class="mention">@Bob</span></strong>"#));
+        assert!(html.contains(r#"<span class="mention">@Screensharer</span>"#));
+        assert!(html.contains(r#"<span class="mention">#general</span>"#));
+        assert!(html.contains(r#"<span class="mention">@5</span>"#));
+        assert!(html.contains(r#"<span class="mention">1970-01-01 00:00 UTC</span>"#));
+    }
+
+    #[test]
+    fn renders_links_and_emojis() {
+        let html = render(
+            &[message(
+                1,
+                "see https://example.com/a?b=1&c=2 <a:wave:42>",
+                json!({}),
+            )],
+            Names::default(),
+        );
+        assert!(html.contains(
+            r#"<a href="https://example.com/a?b=1&amp;c=2">https://example.com/a?b=1&amp;c=2</a>"#
+        ));
+        assert!(html.contains(r#"src="https://cdn.discordapp.com/emojis/42.gif" alt=":wave:""#));
+    }
+
+    #[test]
+    fn renders_attachments() {
+        let attachments = json!({
+            "attachments": [
+                {
+                    "id": "7",
+                    "filename": "proof.png",
+                    "height": 10,
+                    "width": 10,
+                    "proxy_url": "https://media.example.com/proof.png",
+                    "size": 2048,
+                    "url": "https://cdn.example.com/proof.png"
+                },
+                {
+                    "id": "8",
+                    "filename": "log.txt",
+                    "proxy_url": "https://media.example.com/log.txt",
+                    "size": 4096,
+                    "url": "https://cdn.example.com/log.txt",
+                    "content_type": "text/plain"
+                }
+            ]
+        });
+        let html = render(&[message(1, "", attachments)], Names::default());
+        assert!(html.contains(
+            r#"<img class="attachment" src="https://cdn.example.com/proof.png" alt="proof.png">"#
+        ));
+        assert!(html.contains(
+            r#"<a class="file" href="https://cdn.example.com/log.txt">log.txt (4 KB)</a>"#
+        ));
+    }
+
+    #[test]
+    fn renders_messages_with_replies_and_embeds() {
+        let original = message(1, "hello", json!({}));
+        let reply = message(
+            2,
+            "hi",
+            json!({
+                "referenced_message": serde_json::to_value(&original).unwrap(),
+                "edited_timestamp": "2022-01-01T12:05:00+00:00",
+                "embeds": [{
+                    "type": "rich",
+                    "title": "Evidence",
+                    "url": "https://example.com",
+                    "description": "**clip**",
+                    "color": 15844367,
+                    "fields": [{"name": "Ign", "value": "Notch", "inline": false}],
+                    "footer": {"text": "Bridge Scrims"}
+                }]
+            }),
+        );
+        let html = render(&[original, reply], Names::default());
+        assert!(html.contains("<title>screenshare-1</title>"));
+        assert!(html.contains(r#"<p class="info">2 messages</p>"#));
+        assert!(html.contains(r#"<span class="author">Alice#0001</span> <span class="time">2022-01-01 12:00 UTC</span>"#));
+        assert!(
+            html.contains(r##"<a href="#1">Reply to</a> <span class="author">Alice</span> hello"##)
+        );
+        assert!(html.contains(r#"<span class="edited">(edited)</span>"#));
+        assert!(html.contains(r#"style="border-left-color: #F1C40F""#));
+        assert!(html.contains(r#"<a href="https://example.com">Evidence</a>"#));
+        assert!(html.contains("<div><strong>clip</strong></div>"));
+        assert!(html.contains(r#"<div class="embed-field-name">Ign</div><div>Notch</div>"#));
+        assert!(html.contains(r#"<div class="embed-footer">Bridge Scrims</div>"#));
+    }
+}
@@ -9,13 +9,19 @@ use tracing_subscriber::{
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+mod alts;
+mod appeal;
+mod automod;
 mod commands;
 mod consts;
 mod db;
+mod escalation;
 mod handler;
 #[macro_use]
 mod macros;
 mod model;
+mod raid;
+mod scheduler;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -41,7 +47,8 @@ async fn main() -> Result<()> {
     let intents = GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::GUILD_MEMBERS
         | GatewayIntents::GUILDS
-        | GatewayIntents::GUILD_VOICE_STATES;
+        | GatewayIntents::GUILD_VOICE_STATES
+        | GatewayIntents::DIRECT_MESSAGES;
 
     let mut client = Client::builder(&CONFIG.bot_token)
         .application_id(application_id)
@@ -1,6 +1,8 @@
 use std::fmt::Display;
 use std::num::ParseIntError;
+use std::str::FromStr;
 
+use serde::Deserialize;
 use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
 use time::OffsetDateTime;
 
@@ -9,12 +11,235 @@ pub struct Unban {
     pub date: OffsetDateTime,
 }
 
+pub struct Untimeout {
+    pub id: u64,
+    pub date: OffsetDateTime,
+}
+
+/// The kinds of delayed actions run by the scheduler
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum JobKind {
+    /// `target` is the user, ends a server ban
+    Unban,
+    /// `target` is the user, ends a scrim ban
+    ScrimUnban,
+    /// `target` is the user, records the end of a timeout
+    Untimeout,
+    /// `target` is the frozen user, `data` the channel they were frozen in
+    UnfreezeReminder,
+    /// `target` is the user, `data` the role that is removed
+    TempRole,
+    /// `target` is the channel, `data` the message that is sent
+    Reminder,
+    /// `target` is the channel, `data` the `ChannelChange` that is reverted
+    ChannelRevert,
+    /// `target` is the screenshare ticket, closes it if nobody was frozen in time
+    ScreenshareDeadline,
+    /// `target` is the ticket, `data` is `warn` or `close`, the next step for a quiet ticket
+    TicketInactivity,
+    /// `target` is the ticket, `data` the requester and outcome, closes the ticket if the close
+    /// request was not answered
+    CloseRequest,
+    /// `target` is the pending escalation, `data` the confirmation message that expires
+    EscalationExpiry,
+}
+
+impl JobKind {
+    /// The name under which the kind is stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unban => "unban",
+            Self::ScrimUnban => "scrimunban",
+            Self::Untimeout => "untimeout",
+            Self::UnfreezeReminder => "unfreezereminder",
+            Self::TempRole => "temprole",
+            Self::Reminder => "reminder",
+            Self::ChannelRevert => "channelrevert",
+            Self::ScreenshareDeadline => "screensharedeadline",
+            Self::TicketInactivity => "ticketinactivity",
+            Self::CloseRequest => "closerequest",
+            Self::EscalationExpiry => "escalationexpiry",
+        }
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unban" => Ok(Self::Unban),
+            "scrimunban" => Ok(Self::ScrimUnban),
+            "untimeout" => Ok(Self::Untimeout),
+            "unfreezereminder" => Ok(Self::UnfreezeReminder),
+            "temprole" => Ok(Self::TempRole),
+            "reminder" => Ok(Self::Reminder),
+            "channelrevert" => Ok(Self::ChannelRevert),
+            "screensharedeadline" => Ok(Self::ScreenshareDeadline),
+            "ticketinactivity" => Ok(Self::TicketInactivity),
+            "closerequest" => Ok(Self::CloseRequest),
+            "escalationexpiry" => Ok(Self::EscalationExpiry),
+            _ => Err(format!("unknown job kind {}", s)),
+        }
+    }
+}
+
+pub struct ScheduledJob {
+    pub id: u64,
+    pub kind: JobKind,
+    pub target: u64,
+    pub data: String,
+    /// When the job is due
+    pub date: OffsetDateTime,
+    /// How often the job failed before
+    pub attempts: u64,
+}
+
+/// A channel setting that can be changed temporarily
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChannelChange {
+    /// `@everyone` is not allowed to send messages
+    Lockdown,
+    Slowmode,
+}
+
+impl ChannelChange {
+    /// The name under which the change is stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lockdown => "lockdown",
+            Self::Slowmode => "slowmode",
+        }
+    }
+}
+
+impl FromStr for ChannelChange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lockdown" => Ok(Self::Lockdown),
+            "slowmode" => Ok(Self::Slowmode),
+            _ => Err(format!("unknown channel change {}", s)),
+        }
+    }
+}
+
+pub struct ChannelRevert {
+    pub channel: u64,
+    pub change: ChannelChange,
+    /// The setting before the change: the `@everyone` send permission ("allow", "deny" or
+    /// "neutral") for lockdowns, the rate limit in seconds for slowmodes
+    pub previous: String,
+    /// When the change is reverted, if it is reverted automatically
+    pub date: Option<OffsetDateTime>,
+}
+
+pub struct TempRole {
+    pub user: u64,
+    pub role: u64,
+    /// When the role is removed
+    pub date: OffsetDateTime,
+}
+
+/// What a staff member did within a period, see `/modstats`
+#[derive(Default, Clone)]
+pub struct ModStats {
+    pub bans: u64,
+    pub scrim_bans: u64,
+    pub timeouts: u64,
+    pub warnings: u64,
+    pub notes: u64,
+    pub freezes: u64,
+    pub tickets_closed: u64,
+    pub purges: u64,
+}
+
+impl ModStats {
+    pub fn total(&self) -> u64 {
+        self.bans
+            + self.scrim_bans
+            + self.timeouts
+            + self.warnings
+            + self.notes
+            + self.freezes
+            + self.tickets_closed
+            + self.purges
+    }
+}
+
 pub struct ScrimUnban {
     pub id: u64,
     pub date: OffsetDateTime,
     pub roles: Ids,
 }
 
+/// How a screenshare ended
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScreenshareOutcome {
+    /// Nothing was found
+    Clean,
+    Admitted,
+    /// The user refused the screenshare or logged out
+    Refused,
+    Cheating,
+    /// The request itself was not valid
+    Invalid,
+}
+
+impl ScreenshareOutcome {
+    pub const ALL: [Self; 5] = [
+        Self::Clean,
+        Self::Admitted,
+        Self::Refused,
+        Self::Cheating,
+        Self::Invalid,
+    ];
+
+    /// The name under which the outcome is stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Clean => "clean",
+            Self::Admitted => "admitted",
+            Self::Refused => "refused",
+            Self::Cheating => "cheating",
+            Self::Invalid => "invalid",
+        }
+    }
+
+    /// Whether the user in question can be scrimbanned for this outcome
+    pub fn is_bannable(&self) -> bool {
+        matches!(self, Self::Admitted | Self::Refused | Self::Cheating)
+    }
+}
+
+impl Display for ScreenshareOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Clean => "Clean",
+                Self::Admitted => "Admitted",
+                Self::Refused => "Refused / logged",
+                Self::Cheating => "Cheating found",
+                Self::Invalid => "Invalid request",
+            }
+        )
+    }
+}
+
+impl FromStr for ScreenshareOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.as_str() == s)
+            .ok_or_else(|| format!("unknown screenshare outcome {}", s))
+    }
+}
+
 pub struct Screenshare {
     /// Channel ID of the ticket
     pub id: u64,
@@ -22,10 +247,45 @@ pub struct Screenshare {
     pub creator: u64,
     /// User ID of the person being screenshared
     pub in_question: u64,
+    /// Time when the ticket was opened, `None` for tickets from before this was recorded
+    pub created_at: Option<OffsetDateTime>,
+    /// Time when the ticket was closed, `None` while it is still open
+    pub closed_at: Option<OffsetDateTime>,
+    /// User ID of the person who closed the ticket
+    pub closer: Option<u64>,
+    /// The ticket is closed at this time unless the user in question was frozen
+    pub deadline: Option<OffsetDateTime>,
+    /// User ID of the screensharer that claimed the ticket
+    pub claimer: Option<u64>,
+    /// `None` while the ticket is open and for tickets that were closed automatically
+    pub outcome: Option<ScreenshareOutcome>,
+}
+
+/// A ticket of one of the configured ticket types
+pub struct SupportTicket {
+    /// Channel ID of the ticket
+    pub id: u64,
+    /// Name of the ticket type
+    pub kind: String,
+    /// User ID of the person who opened the ticket
+    pub creator: u64,
+}
+
+/// A closed ticket whose transcript was archived
+pub struct ArchivedTranscript {
+    /// Channel ID of the closed ticket
+    pub channel: u64,
+    /// Name of the ticket channel
+    pub title: String,
+    /// Everyone that wrote in the ticket or that the ticket was about
+    pub participants: Vec<u64>,
+    pub outcome: Option<ScreenshareOutcome>,
+    pub closed_at: OffsetDateTime,
 }
 
 pub struct Freeze {
     /// User ID of the person being frozen
+    #[allow(dead_code)]
     pub id: u64,
     /// Their roles
     pub roles: Vec<RoleId>,
@@ -36,6 +296,7 @@ pub struct Freeze {
 #[derive(Debug)]
 pub struct Note {
     /// the id of the person that the note belongs to
+    #[allow(dead_code)]
     pub userid: u64,
     /// the note id
     pub id: u64,
@@ -47,6 +308,174 @@ pub struct Note {
     pub creator: u64,
 }
 
+#[derive(Debug)]
+pub struct Warning {
+    /// the warning id
+    pub id: u64,
+    /// the date that the warning was issued at
+    pub created_at: OffsetDateTime,
+    /// the reason for the warning
+    pub reason: String,
+    /// the id of the staff member who issued the warning
+    pub creator: u64,
+    /// the date after which the warning no longer counts, if any
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+impl Warning {
+    /// Whether the warning still counts against the user
+    pub fn is_active(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expiry| expiry > OffsetDateTime::now_utc())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PunishmentKind {
+    Ban,
+    Unban,
+    ScrimBan,
+    ScrimUnban,
+    Kick,
+    SoftBan,
+    Timeout,
+    Untimeout,
+    Freeze,
+    Unfreeze,
+    Warning,
+}
+
+impl PunishmentKind {
+    /// The name under which the kind is stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ban => "ban",
+            Self::Unban => "unban",
+            Self::ScrimBan => "scrimban",
+            Self::ScrimUnban => "scrimunban",
+            Self::Kick => "kick",
+            Self::SoftBan => "softban",
+            Self::Timeout => "timeout",
+            Self::Untimeout => "untimeout",
+            Self::Freeze => "freeze",
+            Self::Unfreeze => "unfreeze",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+impl Display for PunishmentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Ban => "Ban",
+                Self::Unban => "Unban",
+                Self::ScrimBan => "Scrim ban",
+                Self::ScrimUnban => "Scrim unban",
+                Self::Kick => "Kick",
+                Self::SoftBan => "Softban",
+                Self::Timeout => "Timeout",
+                Self::Untimeout => "Untimeout",
+                Self::Freeze => "Freeze",
+                Self::Unfreeze => "Unfreeze",
+                Self::Warning => "Warning",
+            }
+        )
+    }
+}
+
+impl FromStr for PunishmentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ban" => Ok(Self::Ban),
+            "unban" => Ok(Self::Unban),
+            "scrimban" => Ok(Self::ScrimBan),
+            "scrimunban" => Ok(Self::ScrimUnban),
+            "kick" => Ok(Self::Kick),
+            "softban" => Ok(Self::SoftBan),
+            "timeout" => Ok(Self::Timeout),
+            "untimeout" => Ok(Self::Untimeout),
+            "freeze" => Ok(Self::Freeze),
+            "unfreeze" => Ok(Self::Unfreeze),
+            "warning" => Ok(Self::Warning),
+            _ => Err(format!("unknown punishment kind {}", s)),
+        }
+    }
+}
+
+pub struct Punishment {
+    /// The case number
+    pub id: u64,
+    pub kind: PunishmentKind,
+    /// User ID of the person that was punished
+    pub user: u64,
+    /// User ID of the staff member, `None` if the bot acted on its own (e.g. an expiry)
+    pub staff: Option<u64>,
+    pub reason: String,
+    pub created_at: OffsetDateTime,
+    /// When the punishment ends, `None` if it is permanent or instant
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppealStatus {
+    Open,
+    Accepted,
+    Denied,
+}
+
+impl AppealStatus {
+    /// The name under which the status is stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Accepted => "accepted",
+            Self::Denied => "denied",
+        }
+    }
+}
+
+impl FromStr for AppealStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Self::Open),
+            "accepted" => Ok(Self::Accepted),
+            "denied" => Ok(Self::Denied),
+            _ => Err(format!("unknown appeal status {}", s)),
+        }
+    }
+}
+
+pub struct Appeal {
+    /// User ID of the banned user
+    pub user: u64,
+    /// The kind of ban that is appealed, either `Ban` or `ScrimBan`
+    pub kind: PunishmentKind,
+    /// The case of the appealed ban, if it was recorded
+    pub case: Option<u64>,
+    pub status: AppealStatus,
+}
+
+/// Raid mode is active while this is stored
+pub struct RaidMode {
+    /// The verification level before it was raised for the raid, as its number
+    pub previous_level: Option<u64>,
+}
+
+/// An escalation that waits for staff to confirm it
+pub struct PendingEscalation {
+    pub user: u64,
+    /// The name of the escalation rule that fired
+    pub rule: String,
+}
+
 pub struct Ids(pub Vec<u64>);
 
 impl Display for Ids {
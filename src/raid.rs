@@ -0,0 +1,224 @@
+//! Raid detection, configured in the `[raid]` section of the config.
+//!
+//! Joins are counted in a sliding window. Once too many users join too quickly, the guild enters
+//! raid mode until staff end it. Everyone who joins during a raid is recorded, so that the whole
+//! wave can be banned with `/raid ban`. Raid mode and the verification level it replaced are
+//! stored, so a restart does not leave the guild stuck in raid mode.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serenity::{
+    client::Context,
+    model::{
+        guild::{Member, VerificationLevel},
+        id::UserId,
+        interactions::message_component::ButtonStyle,
+    },
+    utils::Color,
+};
+use time::OffsetDateTime;
+
+use crate::consts::{Raid, RaidAction, CONFIG, DATABASE};
+use crate::db::JobKind;
+
+/// How long new joiners are timed out for when no timeout is configured
+const DEFAULT_TIMEOUT: u64 = 60 * 60;
+
+lazy_static::lazy_static! {
+    /// Recent joins of young accounts
+    static ref JOINS: Mutex<VecDeque<(Instant, UserId)>> = Mutex::new(VecDeque::new());
+}
+
+pub async fn member_joined(ctx: &Context, member: &Member) {
+    let config = match &CONFIG.raid {
+        Some(config) => config,
+        None => return,
+    };
+    let young = config.account_age.is_none_or(|age| {
+        chrono::Utc::now() - member.user.created_at() < chrono::Duration::seconds(age as i64)
+    });
+
+    let (active, wave) = {
+        let mut joins = JOINS.lock().unwrap();
+        if DATABASE.fetch_raid_mode().is_some() {
+            (true, None)
+        } else if young {
+            let now = Instant::now();
+            joins.push_back((now, member.user.id));
+            while joins
+                .front()
+                .is_some_and(|(joined, _)| now - *joined > Duration::from_secs(config.seconds))
+            {
+                joins.pop_front();
+            }
+            if joins.len() >= config.joins {
+                if let Err(e) = DATABASE.set_raid_mode(None) {
+                    tracing::error!("Could not store raid mode: {}", e);
+                }
+                let wave: Vec<UserId> = joins.drain(..).map(|(_, id)| id).collect();
+                (true, Some(wave))
+            } else {
+                (false, None)
+            }
+        } else {
+            (false, None)
+        }
+    };
+
+    match wave {
+        Some(wave) => start(ctx, config, wave).await,
+        None if active => handle_join(ctx, config, member.user.id).await,
+        None => {}
+    }
+}
+
+async fn handle_join(ctx: &Context, config: &Raid, user: UserId) {
+    if let Err(e) = DATABASE.add_raid_member(user.0, OffsetDateTime::now_utc()) {
+        tracing::error!("Could not record raid member {}: {}", user, e);
+    }
+    if config.action != RaidAction::Timeout {
+        return;
+    }
+    let end = chrono::Utc::now()
+        + chrono::Duration::seconds(config.timeout.unwrap_or(DEFAULT_TIMEOUT) as i64);
+    let result = match CONFIG.guild.member(&ctx.http, user).await {
+        Ok(mut member) => {
+            member
+                .disable_communication_until_datetime(&ctx.http, end)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => {
+            if let Ok(until) = OffsetDateTime::from_unix_timestamp(end.timestamp()) {
+                if let Err(e) = DATABASE.add_timeout(user.0, until) {
+                    tracing::error!("Could not store the timeout of {}: {}", user, e);
+                }
+                crate::scheduler::schedule(JobKind::Untimeout, user.0, "", until);
+            }
+        }
+        Err(e) => tracing::error!("Could not time out raid member {}: {}", user, e),
+    }
+}
+
+async fn start(ctx: &Context, config: &'static Raid, wave: Vec<UserId>) {
+    tracing::warn!("Raid detected, {} users joined", wave.len());
+    if let Err(e) = DATABASE.clear_raid_members() {
+        tracing::error!("Could not clear previous raid members: {}", e);
+    }
+    for user in &wave {
+        handle_join(ctx, config, *user).await;
+    }
+    if config.action == RaidAction::Verification {
+        let previous = CONFIG
+            .guild
+            .to_guild_cached(&ctx.cache)
+            .await
+            .map(|x| x.verification_level.num());
+        if let Err(e) = DATABASE.set_raid_mode(previous) {
+            tracing::error!("Could not store the previous verification level: {}", e);
+        }
+        let mut guild = CONFIG.guild;
+        if let Err(e) = guild
+            .edit(&ctx.http, |g| {
+                g.verification_level(VerificationLevel::Higher)
+            })
+            .await
+        {
+            tracing::error!("Could not raise the verification level: {}", e);
+        }
+    }
+    tokio::spawn(alert(ctx.clone(), config, wave));
+}
+
+/// Ends raid mode, returning whether it was active
+pub async fn end(ctx: &Context) -> bool {
+    let previous = {
+        let mut joins = JOINS.lock().unwrap();
+        let mode = match DATABASE.fetch_raid_mode() {
+            Some(mode) => mode,
+            None => return false,
+        };
+        if let Err(e) = DATABASE.remove_entry("RaidMode", 0) {
+            tracing::error!("Could not end raid mode: {}", e);
+        }
+        joins.clear();
+        mode.previous_level
+    };
+    if let Some(level) = previous.and_then(verification_level) {
+        let mut guild = CONFIG.guild;
+        if let Err(e) = guild.edit(&ctx.http, |g| g.verification_level(level)).await {
+            tracing::error!("Could not restore the verification level: {}", e);
+        }
+    }
+    tracing::info!("Raid mode ended");
+    true
+}
+
+fn verification_level(num: u64) -> Option<VerificationLevel> {
+    Some(match num {
+        0 => VerificationLevel::None,
+        1 => VerificationLevel::Low,
+        2 => VerificationLevel::Medium,
+        3 => VerificationLevel::High,
+        4 => VerificationLevel::Higher,
+        _ => return None,
+    })
+}
+
+async fn alert(ctx: Context, config: &'static Raid, wave: Vec<UserId>) {
+    if let Err(e) = send_alert(&ctx, config, wave).await {
+        tracing::error!("Could not send raid alert: {}", e);
+    }
+}
+
+async fn send_alert(ctx: &Context, config: &Raid, wave: Vec<UserId>) -> serenity::Result<()> {
+    let action = match config.action {
+        RaidAction::Verification => "The verification level has been raised.",
+        RaidAction::Timeout => "New joiners are being timed out.",
+    };
+    let mut users = wave
+        .iter()
+        .map(|x| format!("<@{}>", x))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if users.len() > 1000 {
+        let mut end = 1000;
+        while !users.is_char_boundary(end) {
+            end -= 1;
+        }
+        users.truncate(end);
+        users.push_str("...");
+    }
+    config
+        .alerts
+        .unwrap_or(CONFIG.support_bans)
+        .send_message(&ctx.http, |msg| {
+            msg.embed(|embed| {
+                embed
+                    .title("Raid detected")
+                    .description(format!(
+                        "{} users joined within {} seconds. {}\nUse `/raid ban` to ban everyone who joined during the raid.",
+                        wave.len(),
+                        config.seconds,
+                        action
+                    ))
+                    .field("Users", users, false)
+                    .color(Color::RED)
+            })
+            .components(|c| {
+                c.create_action_row(|row| {
+                    row.create_button(|b| {
+                        b.label("End raid mode")
+                            .style(ButtonStyle::Danger)
+                            .custom_id("raid:end")
+                    })
+                })
+            })
+        })
+        .await?;
+    Ok(())
+}
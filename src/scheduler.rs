@@ -0,0 +1,426 @@
+//! Persistent scheduler for delayed actions.
+//!
+//! Jobs are stored in the `ScheduledJobs` table, so they survive restarts. A single task sleeps
+//! until the next job is due and is woken early whenever a job is scheduled. Jobs check that
+//! their action is still needed when they run, so a ban that was lifted by hand is not touched.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serenity::{
+    client::Context,
+    model::id::{ChannelId, MessageId, RoleId, UserId},
+    utils::Color,
+};
+use time::OffsetDateTime;
+use tokio::sync::Notify;
+
+use crate::commands::unban::{UnbanEntry, UnbanType};
+use crate::consts::{CONFIG, DATABASE};
+use crate::db::{JobKind, PunishmentKind, ScheduledJob};
+
+/// How long to wait before running a failed job again
+const RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+/// How often a job is run before it is given up, an hour with [`RETRY_DELAY`]
+const MAX_ATTEMPTS: u64 = 12;
+/// How long to sleep when there are no jobs, unless one is scheduled
+const IDLE: Duration = Duration::from_secs(24 * 60 * 60);
+
+type JobResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// The data of a job could not be parsed, so running it again would fail again
+#[derive(Debug)]
+struct InvalidData(String);
+
+impl fmt::Display for InvalidData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid job data `{}`", self.0)
+    }
+}
+
+impl Error for InvalidData {}
+
+fn parse_data<T: FromStr>(job: &ScheduledJob) -> Result<T, InvalidData> {
+    job.data.parse().map_err(|_| InvalidData(job.data.clone()))
+}
+
+lazy_static::lazy_static! {
+    static ref WAKE: Notify = Notify::new();
+}
+
+/// Schedules a job, replacing the job with the same kind, target and data
+pub fn schedule(kind: JobKind, target: u64, data: &str, date: OffsetDateTime) {
+    if let Err(e) = DATABASE.add_job(kind, target, data, date, 0) {
+        tracing::error!("Could not schedule {:?} job for {}: {}", kind, target, e);
+    }
+    WAKE.notify_one();
+}
+
+pub fn cancel(kind: JobKind, target: u64, data: &str) {
+    if let Err(e) = DATABASE.remove_job(kind, target, data) {
+        tracing::error!("Could not cancel {:?} job for {}: {}", kind, target, e);
+    }
+}
+
+/// Starts the scheduler. Expiries that were stored before the scheduler existed are scheduled
+/// first.
+pub fn start(ctx: Context) {
+    for unban in DATABASE.fetch_unbans() {
+        let _ = DATABASE.add_job(JobKind::Unban, unban.id, "", unban.date, 0);
+    }
+    for unban in DATABASE.fetch_scrim_unbans() {
+        let _ = DATABASE.add_job(JobKind::ScrimUnban, unban.id, "", unban.date, 0);
+    }
+    for timeout in DATABASE.fetch_timeouts() {
+        let _ = DATABASE.add_job(JobKind::Untimeout, timeout.id, "", timeout.date, 0);
+    }
+    for temp_role in DATABASE.fetch_temp_roles() {
+        let _ = DATABASE.add_job(
+            JobKind::TempRole,
+            temp_role.user,
+            &temp_role.role.to_string(),
+            temp_role.date,
+            0,
+        );
+    }
+    for revert in DATABASE.fetch_channel_reverts() {
+        if let Some(date) = revert.date {
+            let _ = DATABASE.add_job(
+                JobKind::ChannelRevert,
+                revert.channel,
+                revert.change.as_str(),
+                date,
+                0,
+            );
+        }
+    }
+    // Deadline jobs are stored with the screenshare, so a deadline without a job was handled
+    // before handled deadlines were cleared
+    let deadlines: Vec<_> = DATABASE
+        .fetch_jobs()
+        .into_iter()
+        .filter(|x| x.kind == JobKind::ScreenshareDeadline)
+        .map(|x| x.target)
+        .collect();
+    for screenshare in DATABASE.fetch_open_screenshares() {
+        if screenshare.deadline.is_some() && !deadlines.contains(&screenshare.id) {
+            let _ = DATABASE.clear_screenshare_deadline(screenshare.id);
+        }
+    }
+    // Tickets that were opened before their type had inactivity rules
+    let watched: Vec<_> = DATABASE
+        .fetch_jobs()
+        .into_iter()
+        .filter(|x| x.kind == JobKind::TicketInactivity)
+        .map(|x| x.target)
+        .collect();
+    let tickets = DATABASE
+        .fetch_open_screenshares()
+        .into_iter()
+        .map(|x| x.id)
+        .chain(DATABASE.fetch_tickets(None).into_iter().map(|x| x.id));
+    for ticket in tickets {
+        if !watched.contains(&ticket)
+            && !DATABASE.is_kept_open(ticket)
+            && crate::commands::ticket::inactivity_rules(ticket).is_some()
+        {
+            let _ = DATABASE.add_job(
+                JobKind::TicketInactivity,
+                ticket,
+                "warn",
+                OffsetDateTime::now_utc(),
+                0,
+            );
+        }
+    }
+    tokio::spawn(run(ctx));
+}
+
+/// Scrim unbans need the user to be in the server, so expired scrim bans of users that left are
+/// run again when they rejoin
+pub fn member_joined(user: UserId) {
+    let now = OffsetDateTime::now_utc();
+    let expired = DATABASE
+        .fetch_scrim_unbans()
+        .iter()
+        .any(|x| x.id == user.0 && x.date <= now);
+    if expired {
+        schedule(JobKind::ScrimUnban, user.0, "", now);
+    }
+}
+
+async fn run(ctx: Context) {
+    loop {
+        let now = OffsetDateTime::now_utc();
+        let jobs = DATABASE.fetch_jobs();
+        let wait = match jobs.first() {
+            Some(job) if job.date <= now => {
+                for job in jobs.iter().take_while(|x| x.date <= now) {
+                    // Retries and reschedules are added as new jobs
+                    let _ = DATABASE.remove_entry("ScheduledJobs", job.id);
+                    match execute(&ctx, job).await {
+                        Ok(()) => {}
+                        Err(e) if e.is::<InvalidData>() => {
+                            tracing::error!(
+                                "Dropping {:?} job for {}: {}",
+                                job.kind,
+                                job.target,
+                                e
+                            );
+                        }
+                        Err(e) if job.attempts + 1 >= MAX_ATTEMPTS => {
+                            tracing::error!(
+                                "{:?} job for {} failed {} times, giving up: {}",
+                                job.kind,
+                                job.target,
+                                MAX_ATTEMPTS,
+                                e
+                            );
+                        }
+                        Err(e) => {
+                            tracing::error!("{:?} job for {} failed: {}", job.kind, job.target, e);
+                            let retry = DATABASE.add_job(
+                                job.kind,
+                                job.target,
+                                &job.data,
+                                now + RETRY_DELAY,
+                                job.attempts + 1,
+                            );
+                            if let Err(e) = retry {
+                                tracing::error!(
+                                    "Could not retry {:?} job for {}: {}",
+                                    job.kind,
+                                    job.target,
+                                    e
+                                );
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+            Some(job) => (job.date - now).try_into().unwrap_or_default(),
+            None => IDLE,
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = WAKE.notified() => {}
+        }
+    }
+}
+
+async fn execute(ctx: &Context, job: &ScheduledJob) -> JobResult {
+    let http = &ctx.http;
+    let now = OffsetDateTime::now_utc();
+    match job.kind {
+        JobKind::Unban => {
+            let unban = match DATABASE
+                .fetch_unbans()
+                .into_iter()
+                .find(|x| x.id == job.target)
+            {
+                Some(unban) => unban,
+                // Unbanned by hand
+                None => return Ok(()),
+            };
+            if unban.date > now {
+                schedule(JobKind::Unban, unban.id, "", unban.date);
+                return Ok(());
+            }
+            CONFIG.guild.unban(http, unban.id).await?;
+            DATABASE.remove_entry("ScheduledUnbans", unban.id)?;
+            crate::commands::case::record(
+                PunishmentKind::Unban,
+                unban.id,
+                None,
+                "Ban Expired",
+                None,
+            );
+        }
+        JobKind::ScrimUnban => {
+            let unban = match DATABASE
+                .fetch_scrim_unbans()
+                .into_iter()
+                .find(|x| x.id == job.target)
+            {
+                Some(unban) => unban,
+                None => return Ok(()),
+            };
+            if unban.date > now {
+                schedule(JobKind::ScrimUnban, unban.id, "", unban.date);
+                return Ok(());
+            }
+            // Users that left are unbanned once they rejoin, see `member_joined`
+            if CONFIG.guild.member(http, unban.id).await.is_err() {
+                return Ok(());
+            }
+            UnbanType::Scrim
+                .unban(
+                    http,
+                    None,
+                    UnbanEntry::Scrim(unban),
+                    "Ban Expired".to_string(),
+                )
+                .await?;
+        }
+        JobKind::Untimeout => {
+            // The timeout was extended, the new timeout has its own job
+            if DATABASE.fetch_timeouts().iter().any(|x| x.id == job.target) {
+                return Ok(());
+            }
+            DATABASE.remove_entry("Timeouts", job.target)?;
+            crate::commands::case::record(
+                PunishmentKind::Untimeout,
+                job.target,
+                None,
+                "Timeout Expired",
+                None,
+            );
+        }
+        JobKind::UnfreezeReminder => {
+            if DATABASE.fetch_freezes_for(job.target).is_none() {
+                return Ok(());
+            }
+            let channel = ChannelId(parse_data(job)?);
+            // Only the screensharer that claimed the ticket is pinged
+            let claimer = DATABASE
+                .fetch_screenshares_for(channel.0)
+                .and_then(|x| x.claimer)
+                .map_or_else(String::new, |x| format!("<@{}> ", x));
+            channel
+                .say(
+                    http,
+                    format!(
+                        "{}Reminder: <@{}> is still frozen. Use `/unfreeze` once the screenshare is over.",
+                        claimer, job.target
+                    ),
+                )
+                .await?;
+        }
+        JobKind::TempRole => {
+            let role = RoleId(parse_data(job)?);
+            let stored = DATABASE
+                .fetch_temp_roles()
+                .into_iter()
+                .find(|x| x.user == job.target && x.role == role.0);
+            match stored {
+                Some(stored) if stored.date > now => {
+                    schedule(JobKind::TempRole, job.target, &job.data, stored.date);
+                    return Ok(());
+                }
+                Some(_) => DATABASE.remove_temp_role(job.target, role.0)?,
+                None => return Ok(()),
+            }
+            // The role is gone together with the member
+            let mut member = match CONFIG.guild.member(http, job.target).await {
+                Ok(member) => member,
+                Err(_) => return Ok(()),
+            };
+            member.remove_role(http, role).await?;
+            CONFIG
+                .mod_logs
+                .unwrap_or(CONFIG.support_bans)
+                .send_message(http, |msg| {
+                    msg.embed(|e| {
+                        e.title("Temporary role expired")
+                            .field("User", format!("<@{}>", job.target), false)
+                            .field("Role", format!("<@&{}>", role), false)
+                            .color(Color::DARK_GREEN)
+                    })
+                })
+                .await?;
+        }
+        JobKind::Reminder => {
+            ChannelId(job.target).say(http, &job.data).await?;
+        }
+        JobKind::ChannelRevert => {
+            crate::commands::lockdown::revert(http, ChannelId(job.target), parse_data(job)?)
+                .await?;
+        }
+        JobKind::ScreenshareDeadline => {
+            let screenshare = match DATABASE.fetch_screenshares_for(job.target) {
+                Some(screenshare) if screenshare.deadline.is_some() => screenshare,
+                // Closed, or the user in question was frozen before the deadline
+                _ => return Ok(()),
+            };
+            // The user may also have been frozen with /freeze
+            if DATABASE
+                .fetch_freezes_for(screenshare.in_question)
+                .is_some()
+            {
+                DATABASE.clear_screenshare_deadline(screenshare.id)?;
+            } else {
+                crate::commands::close::close_ticket(
+                    ctx,
+                    None,
+                    ChannelId(screenshare.id),
+                    None,
+                    Some("Nobody was frozen in time"),
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        JobKind::TicketInactivity => {
+            let channel = ChannelId(job.target);
+            let rules = match crate::commands::ticket::inactivity_rules(channel.0) {
+                Some(rules) if !DATABASE.is_kept_open(channel.0) => rules,
+                // Closed, kept open or the rules were removed from the config
+                _ => return Ok(()),
+            };
+            let warn_after = Duration::from_secs(rules.warn_after);
+            let close_after = Duration::from_secs(rules.close_after);
+            let last = crate::commands::ticket::last_activity(ctx, channel).await?;
+            match job.data.as_str() {
+                "warn" if now - last < warn_after => {
+                    schedule(job.kind, job.target, "warn", last + warn_after);
+                }
+                "warn" => {
+                    let close = now + close_after;
+                    channel
+                        .say(
+                            http,
+                            format!(
+                                "{}This ticket has been inactive for a while and will be closed <t:{}:R> unless someone writes here.",
+                                crate::commands::ticket::participants(channel.0),
+                                close.unix_timestamp()
+                            ),
+                        )
+                        .await?;
+                    schedule(job.kind, job.target, "close", close);
+                }
+                // Someone wrote after the warning
+                _ if last > job.date - close_after => {
+                    schedule(job.kind, job.target, "warn", last + warn_after);
+                }
+                _ => {
+                    crate::commands::close::close_ticket(
+                        ctx,
+                        None,
+                        channel,
+                        None,
+                        Some("Inactivity"),
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        JobKind::EscalationExpiry => {
+            crate::escalation::expire(ctx, job.target, MessageId(parse_data(job)?)).await?;
+        }
+        JobKind::CloseRequest => {
+            crate::commands::close::close_requested(
+                ctx,
+                ChannelId(job.target),
+                &job.data,
+                "Close request not answered",
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}